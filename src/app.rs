@@ -1,11 +1,11 @@
-use std::error;
+use std::{collections::HashMap, error, path::PathBuf, time::SystemTime};
 
 use ratatui::{buffer::Buffer, layout::Rect};
 
 use gilrs::EventType as GamepadEventType;
 use tokio::sync::mpsc;
 
-use crate::{event::Event, pages::*, tasks::{ControllerTask, DriverEvent, DriverTask}};
+use crate::{event::Event, page_functions::{binding_configs, controller_configs, driver_configs, parameter_configs, port_control_configs, scan_configs}, pages::*, protocol::{ParamInfo, ParamValue}, tasks::{ControllerStatus, ControllerTask, DriverError, DriverEvent, DriverPort, DriverRegistry, FirmwareError, FirmwareImage, ParamRequest, ProbeResult, TelemetrySample}, teleop::{key_help, Action, ActionEvent, ActionMapper, BindingAction, Bindings, DriveInput, Input, KeyboardDrive, Kinematics, Stage}};
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
@@ -23,8 +23,23 @@ pub struct App {
     controller_telem: ControllerTelem,
     driver_telem: DriverTelem,
     control_panel: ControlPanel,
+    serial_console: SerialConsole,
+    parameters: ParametersPage,
+    robot_log: RobotLog,
+    axis_tuning: AxisTuning,
+    prompt: Option<Prompt>,
     controller_task: ControllerTask,
-    driver_task: DriverTask,
+    driver_registry: DriverRegistry,
+    drive_input: DriveInput,
+    keyboard: KeyboardDrive,
+    bindings: Bindings,
+    mapper: ActionMapper,
+    /// UUID of the followed pad while it is plugged in.
+    pad_uuid: Option<String>,
+    /// Action waiting for the next pad input to be bound to it.
+    capturing: Option<Action>,
+    /// Latest telemetry from each driver, by driver name.
+    telemetry: HashMap<String, TelemetrySample>,
     sender: Option<mpsc::UnboundedSender<Event>>,
 }
 
@@ -40,8 +55,20 @@ impl Default for App {
             controller_telem: ControllerTelem::new(),
             driver_telem: DriverTelem::new(),
             control_panel: ControlPanel::new(),
+            serial_console: SerialConsole::new(),
+            parameters: ParametersPage::new(),
+            robot_log: RobotLog::new(),
+            axis_tuning: AxisTuning::new(),
+            prompt: None,
             controller_task: ControllerTask::new(),
-            driver_task: DriverTask::new(),
+            driver_registry: DriverRegistry::new(),
+            drive_input: DriveInput::new(),
+            keyboard: KeyboardDrive::new(),
+            bindings: Bindings::new(),
+            mapper: ActionMapper::default(),
+            pad_uuid: None,
+            capturing: None,
+            telemetry: HashMap::new(),
             sender: None,
         }
     }
@@ -58,16 +85,29 @@ pub enum Mode {
 impl App {
     /// Constructs a new instance of [`App`].
     pub fn new() -> Self {
-        Self::default()
+        let mut app = Self::default();
+        app.binding_action(BindingAction::Reload);
+        app
     }
 
     pub fn add_sender(&mut self, sender: &mpsc::UnboundedSender<Event>) {
         self.sender = Some(sender.clone());
-        self.driver_task.set_sender(sender.clone());
+        self.driver_registry.set_sender(sender.clone());
+        if let Err(e) = self.controller_task.start(sender.clone()) {
+            self.control_panel.set_status(format!("Controller error: {}", e));
+        }
     }
 
     /// Handles the tick event of the terminal.
-    pub fn tick(&self) {}
+    pub fn tick(&mut self) {
+        // The filter and slew stages, and keyboard ramping, keep moving between input events.
+        let now = SystemTime::now();
+        let mut actions = self.mapper.tick(now);
+        actions.extend(self.keyboard.tick(now));
+        if self.apply_actions(actions) {
+            self.driver_registry.set_drive(self.drive_input.command());
+        }
+    }
 
     pub fn is_running(&self) -> bool {
         self.mode != Mode::Quit
@@ -76,6 +116,7 @@ impl App {
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
         self.mode = Mode::Quit;
+        self.controller_task.shutdown();
     }
 
     pub fn set_x_y(&mut self, x: u16, y: u16) {
@@ -111,45 +152,447 @@ impl App {
             match result {
                 ControlResult::ChangePage(page) => self.change_page(page),
                 ControlResult::SetController(controller) => {
-                    self.controller_task.add_sender(self.sender.as_ref().unwrap());
-                    self.controller_task.add_controller(controller);
-                    self.controller_task.add_task();
+                    if let Err(e) = self.controller_task.follow(controller) {
+                        self.control_panel.set_status(format!("Controller error: {}", e));
+                    }
                 },
+                ControlResult::ListControllers => {
+                    if let Err(e) = self.controller_task.list() {
+                        self.control_panel.set_status(format!("Controller error: {}", e));
+                    }
+                },
+                ControlResult::DriverChange(DriverEvent::SetPort(port)) => self.connect_driver(port),
                 ControlResult::DriverChange(event) => {
-                    match event {
-                        DriverEvent::SetPort(port) => {
-                            self.driver_task.set_port(port);
-                            self.driver_task.start_driver();
+                    let result = match event {
+                        DriverEvent::SetLine(setting) => {
+                            self.driver_registry.set_line(setting);
+                            self.update_port_window();
+                            Ok(())
                         }
-                        _ => ()
-                    }
+                        DriverEvent::StateChange(state) => self.driver_registry.request_state(state),
+                        DriverEvent::Reset => self.driver_registry.reset(),
+                        DriverEvent::SetDriveRate(rate_hz) => {
+                            self.driver_registry.set_drive_rate(rate_hz);
+                            Ok(())
+                        }
+                        _ => Ok(())
+                    };
+                    self.driver_status(result);
+                }
+                ControlResult::StartDriver(port) => {
+                    let result = self.driver_registry.start(port);
+                    self.driver_status(result);
+                }
+                ControlResult::StopDriver(port) => {
+                    let result = self.driver_registry.stop(&port);
+                    self.driver_status(result);
+                }
+                ControlResult::SelectDriver(port) => {
+                    let result = self.driver_registry.select(&port);
+                    self.driver_status(result);
                 }
+                ControlResult::OpenPrompt(purpose) => self.prompt = Some(Prompt::new(purpose)),
+                ControlResult::UploadFirmware(path) => self.upload_firmware(&path),
+                ControlResult::ScanForRobots => match self.driver_registry.scan() {
+                    Ok(()) => self.control_panel.set_status("Scanning serial ports for robots".to_string()),
+                    Err(e) => self.control_panel.set_status(format!("Scan failed: {}", e)),
+                },
+                ControlResult::Param(action) => self.param_action(action),
+                ControlResult::Console(action) => self.serial_console.apply(action),
+                ControlResult::Log(action) => self.robot_log.apply(action),
+                ControlResult::Binding(action) => self.binding_action(action),
+                ControlResult::ToggleInvert(action) => {
+                    let invert = !self.mapper.profile().conditioning(action).invert;
+                    self.tune(action, Stage::Invert, &invert.to_string());
+                },
+                ControlResult::DriveMode(mode) => self.set_kinematics(Kinematics { mode, ..self.drive_input.kinematics() }),
+                ControlResult::ToggleKeyboardDrive => self.toggle_keyboard_drive(),
+                ControlResult::ToggleFieldOriented => {
+                    let kinematics = self.drive_input.kinematics();
+                    self.set_kinematics(Kinematics { field_oriented: !kinematics.field_oriented, ..kinematics });
+                },
             }
         }
     }
 
+    /// Starts the driver on a serial port or network endpoint and points the controls at it.
+    fn connect_driver(&mut self, port: String) {
+        let result = self.driver_registry.start(port.clone())
+            .and_then(|_| self.driver_registry.select(&port));
+        if result.is_ok() {
+            // Network drivers aren't enumerated, so list them next to the serial ports.
+            let mut ports = DriverPort::get_ports().unwrap_or_default();
+            ports.extend(self.driver_registry.ports().into_iter().filter(|port| port.contains("://")));
+            self.control_panel.update_window("Drivers", "Drivers".to_string(), driver_configs(ports));
+        }
+        self.driver_status(result);
+    }
+
+    /// Shows the active driver's line settings in the port control windows.
+    fn update_port_window(&mut self) {
+        let line = self.driver_registry.line_settings();
+        self.control_panel.update_window("Driver Port Control", "Driver Port Control".to_string(), port_control_configs(&line));
+    }
+
+    fn driver_status(&mut self, result: Result<(), DriverError>) {
+        self.update_port_window();
+        self.driver_telem.set_active(self.driver_registry.active_name());
+        self.serial_console.set_source(self.driver_registry.active_name());
+        if self.parameters.source() != self.driver_registry.active_name() {
+            self.parameters.set_source(self.driver_registry.active_name());
+            self.update_parameters_window();
+        }
+        match result {
+            Ok(()) => self.control_panel.set_status(self.driver_registry.describe()),
+            Err(e) => self.control_panel.set_status(format!("Driver error: {}", e)),
+        }
+    }
+
+    /// Reads the image on the blocking pool; [`App::handle_firmware`] uploads it once read.
+    fn upload_firmware(&mut self, path: &str) {
+        let Some(sender) = self.sender.clone() else {
+            return self.control_panel.set_status("Firmware: event loop not started".to_string());
+        };
+        self.control_panel.set_status(format!("Firmware: reading {}", path));
+        let path = PathBuf::from(path);
+        tokio::task::spawn_blocking(move || {
+            let _ = sender.send(Event::Firmware(FirmwareImage::load(&path)));
+        });
+    }
+
+    pub fn handle_firmware(&mut self, image: Result<FirmwareImage, FirmwareError>) {
+        let image = match image {
+            Ok(image) => image,
+            Err(e) => return self.control_panel.set_status(format!("Firmware: {}", e)),
+        };
+        let base = image.base_address.map_or(String::new(), |base| format!(" at {:#010x}", base));
+        let status = format!("Firmware: uploading {}{} ({} bytes, CRC {:#010x})", image.name, base, image.data.len(), image.crc());
+        match self.driver_registry.upload_firmware(image) {
+            Ok(()) => self.control_panel.set_status(status),
+            Err(e) => self.control_panel.set_status(format!("Firmware: {}", e)),
+        }
+    }
+
+    fn param_action(&mut self, action: ParamAction) {
+        let request = match action {
+            ParamAction::Refresh => Ok(ParamRequest::List),
+            ParamAction::Save => Ok(ParamRequest::Save),
+            ParamAction::Read(name) => self.find_param(&name).map(|info| ParamRequest::Get(info.index)),
+            ParamAction::Toggle(name) => self.find_param(&name).and_then(|info| match info.value {
+                ParamValue::Bool(value) => Ok(ParamRequest::Set(info.index, ParamValue::Bool(!value))),
+                _ => Err(format!("{} is not a bool", name)),
+            }),
+            ParamAction::ResetDefault(name) => self.find_param(&name).map(|info| ParamRequest::Set(info.index, info.default)),
+            ParamAction::Set(name, text) => self.find_param(&name).and_then(|info| {
+                let value = info.value.parse_like(&text)?;
+                if !info.in_range(&value) {
+                    return Err(format!("{} must be within {} .. {}", name, info.min, info.max));
+                }
+                Ok(ParamRequest::Set(info.index, value))
+            }),
+        };
+        let result = request.and_then(|request| self.driver_registry.request_param(request).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            self.control_panel.set_status(format!("Parameters: {}", e));
+            self.parameters.set_message(e, true);
+        }
+    }
+
+    fn find_param(&self, name: &str) -> Result<ParamInfo, String> {
+        self.parameters.find(name).cloned().ok_or_else(|| format!("no parameter named {}", name))
+    }
+
+    fn update_parameters_window(&mut self) {
+        self.control_panel.update_window("Parameters", "Parameters".to_string(), parameter_configs(self.parameters.names()));
+    }
+
+    pub fn is_prompting(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    pub fn prompt_input(&mut self, c: char) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.push(c);
+        }
+    }
+
+    pub fn prompt_backspace(&mut self) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.backspace();
+        }
+    }
+
+    pub fn prompt_cancel(&mut self) {
+        self.prompt = None;
+    }
+
+    pub fn prompt_submit(&mut self) {
+        let Some(prompt) = self.prompt.take() else { return };
+        match prompt.purpose() {
+            PromptPurpose::ConsoleSend(format) => {
+                let result = format.parse(prompt.text())
+                    .and_then(|bytes| self.driver_registry.send_raw(bytes).map_err(|e| e.to_string()));
+                if let Err(e) = result {
+                    self.control_panel.set_status(format!("Console send failed: {}", e));
+                    self.serial_console.set_error(e);
+                }
+            }
+            PromptPurpose::FirmwarePath => self.upload_firmware(prompt.text().trim()),
+            PromptPurpose::ParamValue(name) => self.param_action(ParamAction::Set(name.clone(), prompt.text().to_string())),
+            PromptPurpose::NetworkAddress(protocol) => self.connect_driver(protocol.endpoint(prompt.text().trim())),
+            PromptPurpose::Conditioning(action, stage) => self.tune(*action, *stage, prompt.text()),
+            PromptPurpose::LogSearch => self.robot_log.apply(LogAction::Search(prompt.text().to_string())),
+            PromptPurpose::KeyboardRamp => {
+                let result = prompt.text().trim().parse::<f32>()
+                    .map_err(|_| format!("'{}' is not a number", prompt.text().trim()))
+                    .and_then(|ramp| self.keyboard.set_ramp(ramp));
+                match result {
+                    Ok(()) => self.show_key_mode(),
+                    Err(e) => self.control_panel.set_status(format!("Keyboard ramp: {}", e)),
+                }
+            },
+        }
+    }
+
+    /// Whether the terminal reports key releases, which keyboard driving then waits for.
+    pub fn set_key_releases(&mut self, reported: bool) {
+        self.keyboard.set_reports_releases(reported);
+    }
+
+    pub fn is_keyboard_driving(&self) -> bool {
+        self.keyboard.is_enabled()
+    }
+
+    /// Switches the keyboard between the menus and driving; leaving drive mode stops what it was driving.
+    pub fn toggle_keyboard_drive(&mut self) {
+        let actions = self.keyboard.set_enabled(!self.keyboard.is_enabled());
+        if self.apply_actions(actions) {
+            self.driver_registry.set_drive(self.drive_input.command());
+        }
+        self.show_key_mode();
+    }
+
+    fn show_key_mode(&mut self) {
+        if self.keyboard.is_enabled() {
+            self.control_panel.set_key_mode(Some(format!(" KEYBOARD DRIVING, ramp {}/s (Tab for menu) ", self.keyboard.ramp())));
+            self.control_panel.set_status(format!("Keys: {}, [ ] ramp, Space e-stop", key_help()));
+        } else {
+            self.control_panel.set_key_mode(None);
+            self.control_panel.set_status("Keyboard back on the menus (Tab to drive)".to_string());
+        }
+    }
+
+    /// A key went down, repeated or, on terminals that report it, came up while driving.
+    pub fn keyboard_drive_key(&mut self, key: char, pressed: bool) {
+        let before = self.keyboard.ramp();
+        let actions = if pressed {
+            self.keyboard.press(key, SystemTime::now())
+        } else {
+            self.keyboard.release(key, SystemTime::now())
+        };
+        if self.apply_actions(actions) {
+            self.driver_registry.set_drive(self.drive_input.command());
+        }
+        if self.keyboard.ramp() != before {
+            self.show_key_mode();
+        }
+    }
+
+    /// Latches every robot into e-stop, whatever the control panel is doing.
+    pub fn estop(&mut self) {
+        match self.driver_registry.estop_all() {
+            Ok(()) => self.control_panel.set_status("E-stop requested".to_string()),
+            Err(e) => self.control_panel.set_status(format!("E-stop failed: {}", e)),
+        }
+    }
+
     pub fn render_current_page(&self, area: Rect, buf: &mut Buffer) {
         match self.page {
             Page::Startup => self.startup_page.render(area, buf),
             Page::ControllerTelem => self.controller_telem.render(area, buf),
-            Page::DriverTelem => self.driver_telem.render(area, buf),
+            Page::DriverTelem => self.driver_telem.render(area, buf, self.active_telemetry()),
+            Page::SerialConsole => self.serial_console.render(area, buf),
+            Page::Parameters => self.parameters.render(area, buf),
+            Page::RobotLog => self.robot_log.render(area, buf),
+            Page::AxisTuning => self.axis_tuning.render(area, buf, &self.mapper),
         }
     }
 
     pub fn render_terminal_page(&mut self, area: Rect, buf: &mut Buffer) {
          self.control_panel.render(area, buf);
+         if let Some(prompt) = self.prompt.as_ref() {
+             prompt.render(Rect { height: area.height.min(3), ..area }, buf);
+         }
     }
 
-    pub fn handle_controller_event(&mut self, event: GamepadEventType){
-        self.controller_telem.add_telem(event);
+    pub fn handle_controller_event(&mut self, event: GamepadEventType, time: SystemTime){
+        if let Some(action) = self.capturing {
+            if let Some(input) = Input::captured(&event) {
+                // The input that was captured is not also acted on.
+                self.capturing = None;
+                let displaced = self.bindings.rebind(self.pad_uuid.as_deref(), action, input);
+                self.apply_profile();
+                self.control_panel.set_status(match displaced {
+                    Some(other) => format!("{} bound to {}; {} is now unbound", action, input, other),
+                    None => format!("{} bound to {}", action, input),
+                });
+                self.controller_telem.add_telem(event, time);
+                return;
+            }
+        }
+        let actions = self.mapper.map(&event, time);
+        let mut changed = self.apply_actions(actions);
+        // A pad that goes away must not leave the last stick deflection applied; the
+        // mapper has already let go of its inputs, so this stops the command too.
+        if event == GamepadEventType::Disconnected {
+            changed |= self.drive_input.reset();
+        }
+        if changed {
+            self.driver_registry.set_drive(self.drive_input.command());
+        }
+        self.controller_telem.add_telem(event, time);
+    }
+
+    /// Acts on mapped pad input, returning whether the drive command changed.
+    fn apply_actions(&mut self, actions: Vec<ActionEvent>) -> bool {
+        let mut changed = false;
+        for action in actions {
+            if action == ActionEvent::Pressed(Action::Estop) {
+                self.estop();
+            }
+            changed |= self.drive_input.update(&action);
+        }
+        changed
+    }
+
+    fn set_kinematics(&mut self, kinematics: Kinematics) {
+        if self.drive_input.set_kinematics(kinematics) {
+            self.driver_registry.set_drive(self.drive_input.command());
+        }
+        self.controller_telem.set_drive_mode(kinematics.describe());
+        self.control_panel.set_status(format!("Drive mode: {}", kinematics.describe()));
+    }
+
+    /// Changes one conditioning stage on the pad's profile.
+    fn tune(&mut self, action: Action, stage: Stage, text: &str) {
+        let profile = self.bindings.profile_mut(self.pad_uuid.as_deref());
+        let mut conditioning = profile.conditioning(action);
+        match conditioning.set(stage, text) {
+            Ok(()) => {
+                profile.set_conditioning(action, conditioning);
+                self.apply_profile();
+                self.control_panel.set_status(format!("{} {} set to {}", action, stage, conditioning.get(stage)));
+            },
+            Err(e) => self.control_panel.set_status(format!("{} {}: {}", action, stage, e)),
+        }
+    }
+
+    fn binding_action(&mut self, action: BindingAction) {
+        match action {
+            BindingAction::Capture(action) => {
+                self.capturing = Some(action);
+                self.control_panel.set_status(format!("Move or press the input for {}", action));
+            },
+            BindingAction::CancelCapture => {
+                self.capturing = None;
+                self.control_panel.set_status("Capture cancelled".to_string());
+            },
+            BindingAction::Save => match self.bindings.save(&Bindings::path()) {
+                Ok(()) => self.control_panel.set_status(format!("Bindings saved to {}", Bindings::path().display())),
+                Err(e) => self.control_panel.set_status(e.to_string()),
+            },
+            BindingAction::Reload => match Bindings::load(&Bindings::path()) {
+                Ok(bindings) => {
+                    self.bindings = bindings;
+                    self.apply_profile();
+                },
+                Err(e) => self.control_panel.set_status(e.to_string()),
+            },
+        }
+    }
+
+    /// Maps the pad through its own profile, or the default, and shows it on the Bindings menu.
+    fn apply_profile(&mut self) {
+        let uuid = self.pad_uuid.as_deref().filter(|uuid| self.bindings.has_profile(uuid));
+        let profile = self.bindings.profile(uuid.unwrap_or_default());
+        self.mapper.set_profile(profile.clone());
+        self.control_panel.update_window("Bindings", "Bindings".to_string(), binding_configs(profile, uuid.unwrap_or("default")));
+    }
+
+    pub fn handle_controllers(&mut self, names: Vec<String>) {
+        self.control_panel.update_window("List Controllers", "Controllers".to_string(), controller_configs(&names, false));
+        self.control_panel.update_window("Connect Controller", "Controllers".to_string(), controller_configs(&names, true));
+    }
+
+    pub fn handle_controller_status(&mut self, status: ControllerStatus) {
+        self.control_panel.set_status(status.to_string());
+        if let ControllerStatus::Connected { uuid, .. } = &status {
+            self.pad_uuid = Some(uuid.clone());
+            self.apply_profile();
+        }
+        self.controller_telem.set_status(status);
     }
 
     fn change_page(&mut self, page: Page){
         self.page = page;
     }
 
-    pub fn handle_driver_event(&mut self, event: DriverEvent) {
-        self.driver_telem.add_telem(event);
+    pub fn telemetry(&self, driver: &str) -> Option<&TelemetrySample> {
+        self.telemetry.get(driver)
+    }
+
+    /// Latest telemetry from the driver the controls apply to.
+    pub fn active_telemetry(&self) -> Option<&TelemetrySample> {
+        self.telemetry.get(self.driver_registry.active_name()?)
+    }
+
+    pub fn handle_scan(&mut self, results: Vec<ProbeResult>) {
+        let found = results.iter().filter(|result| result.outcome.is_ok()).count();
+        self.control_panel.set_status(format!("Scan found {} robot(s) on {} free port(s)", found, results.len()));
+        self.control_panel.update_window("Scan for Robots", "Scan for Robots".to_string(), scan_configs(&results));
+    }
+
+    pub fn handle_driver_event(&mut self, name: String, event: DriverEvent) {
+        let active = self.driver_registry.active_name() == Some(name.as_str());
+        match event {
+            DriverEvent::Raw(chunk) => {
+                if self.serial_console.source() == Some(name.as_str()) {
+                    self.serial_console.add_chunk(chunk);
+                }
+            },
+            DriverEvent::Telemetry(sample) => {
+                // Field-oriented drive steers by the robot being driven.
+                if active && self.drive_input.set_heading(sample.telemetry.heading_deg) {
+                    self.driver_registry.set_drive(self.drive_input.command());
+                }
+                self.telemetry.insert(name, sample);
+            },
+            DriverEvent::RawFailed(reason) => {
+                if self.serial_console.source() == Some(name.as_str()) {
+                    self.control_panel.set_status(format!("Console send failed: {}", reason));
+                    self.serial_console.set_error(reason);
+                }
+            },
+            DriverEvent::Log(entry) => self.robot_log.add_entry(name, entry),
+            DriverEvent::Param(event) => {
+                if self.parameters.source() == Some(name.as_str()) && self.parameters.apply(event) {
+                    self.update_parameters_window();
+                }
+            },
+            event => {
+                match &event {
+                    DriverEvent::UploadProgress(progress) if progress.is_finished() => {
+                        self.control_panel.set_status(format!("Firmware on {}: {}", name, progress));
+                    },
+                    // A robot that just said hello may have rebooted with new parameters.
+                    DriverEvent::Identity(_) if active => {
+                        let _ = self.driver_registry.request_param(ParamRequest::List);
+                    },
+                    _ => (),
+                }
+                self.driver_telem.add_telem(name, event);
+            },
+        }
     }
 
 }
@@ -0,0 +1,59 @@
+use std::{env, process, thread, time::Duration};
+
+use nightmare_gs::{emulator::{EmulatorConfig, RobotEmulator}, tasks::NetProtocol};
+
+const USAGE: &str = "usage: robot_emulator [--drop <rate>] [--corrupt <rate>] [--latency-ms <ms>] [--name <name>] [--listen <tcp|udp>]";
+
+fn main() {
+    let mut config = EmulatorConfig::default();
+    let mut listen = None;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let Some(value) = args.next() else {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        };
+        let parsed = match flag.as_str() {
+            "--drop" => value.parse().map(|rate| config.faults.drop_byte_rate = rate).is_ok(),
+            "--corrupt" => value.parse().map(|rate| config.faults.corrupt_frame_rate = rate).is_ok(),
+            "--latency-ms" => value.parse().map(|ms| config.faults.latency = Duration::from_millis(ms)).is_ok(),
+            "--name" => {
+                config.identity.name = value;
+                true
+            },
+            "--listen" => {
+                listen = match value.as_str() {
+                    "tcp" => Some(NetProtocol::Tcp),
+                    "udp" => Some(NetProtocol::Udp),
+                    _ => None,
+                };
+                listen.is_some()
+            },
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+
+    let spawned = match listen {
+        Some(protocol) => RobotEmulator::spawn_network(protocol, config).map_err(|e| format!("failed to open socket: {}", e)),
+        None => RobotEmulator::spawn(config).map_err(|e| format!("failed to open pseudo-terminal: {}", e)),
+    };
+    let emulator = match spawned {
+        Ok(emulator) => emulator,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    println!("Robot emulator listening on {}", emulator.path());
+    match listen {
+        Some(protocol) => println!("Enter its address under Driver Port Control > Connect {}, press Ctrl-C to stop.", protocol),
+        None => println!("Select it under Driver Port Control, press Ctrl-C to stop."),
+    }
+    loop {
+        thread::park();
+    }
+}
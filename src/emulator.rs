@@ -0,0 +1,541 @@
+//! A stand-in robot on the far end of a pseudo-terminal.
+//!
+//! [`RobotEmulator`] opens a PTY pair and speaks the ground-station protocol on the
+//! master side, so the driver can be pointed at [`RobotEmulator::path`] and exercised
+//! without hardware. Faults can be injected on everything the emulated robot sends.
+//! It also has a bootloader, so firmware uploads can be exercised end to end.
+//! The same robot can listen on a localhost TCP or UDP port instead, standing in
+//! for a Wi-Fi bridge.
+
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serialport::{SerialPort, TTYPort};
+
+use crate::{
+    protocol::{crc32, DriveCommand, FaultFlags, FrameDecoder, LogLevel, Message, MotorTelemetry, Packet, ParamInfo, ParamValue, RobotIdentity, RobotTelemetry},
+    tasks::{DriverState, NetProtocol},
+};
+
+const POLL_TIMEOUT: Duration = Duration::from_millis(5);
+/// Nack code sent when a command is refused because the robot is e-stopped.
+pub const NACK_LATCHED: u8 = 1;
+/// Nack code sent for a flash write outside the emulated flash.
+pub const NACK_FLASH_RANGE: u8 = 2;
+/// Nack code sent for flash requests while the bootloader is not running.
+pub const NACK_NOT_IN_BOOTLOADER: u8 = 3;
+/// Nack code sent for a parameter set outside its range.
+pub const NACK_PARAM_RANGE: u8 = 4;
+/// Nack code sent for a parameter index the robot doesn't have.
+pub const NACK_PARAM_UNKNOWN: u8 = 5;
+/// Nack code sent for an image linked somewhere other than the application start.
+pub const NACK_WRONG_BASE: u8 = 6;
+const FLASH_SIZE: usize = 1024 * 1024;
+/// Encoder rate of a motor at full command.
+const TICKS_PER_SECOND: f32 = 2000.0;
+/// How fast the chassis spins with one side full forward and the other full back.
+const TURN_DEG_PER_SECOND: f32 = 180.0;
+const FULL_BATTERY_V: f32 = 12.6;
+
+/// Fault injection applied to everything the emulated robot sends.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FaultConfig {
+    /// Probability that any single outgoing byte is dropped.
+    pub drop_byte_rate: f64,
+    /// Probability that an outgoing frame has a bit flipped so its CRC fails.
+    pub corrupt_frame_rate: f64,
+    /// Delay added before every outgoing frame.
+    pub latency: Duration,
+    /// Stop answering altogether, as a hung robot would.
+    pub silent: bool,
+    /// Fault flags the robot raises in its telemetry.
+    pub raised: FaultFlags,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmulatorConfig {
+    pub identity: RobotIdentity,
+    pub faults: FaultConfig,
+    pub heartbeat_period: Duration,
+    pub telemetry_period: Duration,
+    /// Largest flash write the bootloader accepts.
+    pub boot_max_chunk: u16,
+    /// Address the application is linked at; flash offsets count from here.
+    pub app_base: u32,
+    pub params: Vec<ParamInfo>,
+    /// How much faster than real time the robot's clock runs, in parts per million.
+    pub clock_drift_ppm: f64,
+    /// Motors reported in telemetry; even ones are on the left, odd ones on the right.
+    pub motors: usize,
+}
+
+fn param(index: u16, name: &str, value: ParamValue, min: ParamValue, max: ParamValue) -> ParamInfo {
+    ParamInfo { index, name: name.to_string(), value, min, max, default: value }
+}
+
+/// A small table shaped like the real robot's.
+fn default_params() -> Vec<ParamInfo> {
+    vec![
+        param(0, "drive.kp", ParamValue::Float(1.2), ParamValue::Float(0.0), ParamValue::Float(10.0)),
+        param(1, "drive.ki", ParamValue::Float(0.05), ParamValue::Float(0.0), ParamValue::Float(1.0)),
+        param(2, "drive.max_speed", ParamValue::Float(2.5), ParamValue::Float(0.0), ParamValue::Float(5.0)),
+        param(3, "imu.yaw_offset", ParamValue::Int(0), ParamValue::Int(-1800), ParamValue::Int(1800)),
+        param(4, "arm.enabled", ParamValue::Bool(true), ParamValue::Bool(false), ParamValue::Bool(true)),
+    ]
+}
+
+impl Default for EmulatorConfig {
+    fn default() -> Self {
+        Self {
+            identity: RobotIdentity {
+                name: "nightmare-emulator".to_string(),
+                firmware: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            faults: FaultConfig::default(),
+            heartbeat_period: Duration::from_millis(100),
+            telemetry_period: Duration::from_millis(100),
+            boot_max_chunk: 256,
+            app_base: 0x0800_4000,
+            params: default_params(),
+            clock_drift_ppm: 0.0,
+            motors: 2,
+        }
+    }
+}
+
+/// What the emulated robot has seen so far, for assertions in tests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmulatorSnapshot {
+    pub state: DriverState,
+    pub last_drive: Option<DriveCommand>,
+    pub drives_received: u64,
+    /// Drive frames that asked for anything other than [`DriveCommand::neutral`].
+    pub moving_drives_received: u64,
+    pub frames_received: u64,
+    pub frames_corrupt: u64,
+    /// Length and CRC-32 of the image the robot last rebooted into.
+    pub firmware: Option<(usize, u32)>,
+    pub reboots: u32,
+    /// Parameter values as last saved to flash.
+    pub saved_params: Option<Vec<ParamValue>>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    stop: AtomicBool,
+    faults: Mutex<FaultConfig>,
+    snapshot: Mutex<EmulatorSnapshot>,
+}
+
+/// Handle on a running emulator; dropping it stops the robot and closes the PTY.
+#[derive(Debug)]
+pub struct RobotEmulator {
+    path: String,
+    shared: Arc<Shared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl RobotEmulator {
+    pub fn spawn(config: EmulatorConfig) -> Result<Self, serialport::Error> {
+        let (mut master, slave) = TTYPort::pair()?;
+        master.set_timeout(POLL_TIMEOUT)?;
+        let path = slave.name().ok_or_else(|| serialport::Error::new(
+            serialport::ErrorKind::Unknown, "pseudo-terminal has no name"))?;
+        // Holding the slave open keeps the master readable between ground connections.
+        Ok(Self::start(path, RobotIo::Pty { master, _slave: slave }, config))
+    }
+
+    /// Listens on a free localhost port; [`RobotEmulator::path`] is then the endpoint,
+    /// e.g. `tcp://127.0.0.1:40000`, to give the driver.
+    pub fn spawn_network(protocol: NetProtocol, config: EmulatorConfig) -> io::Result<Self> {
+        Self::spawn_network_at(protocol, "127.0.0.1:0", config)
+    }
+
+    /// Like [`RobotEmulator::spawn_network`] on a given address, e.g. to bring a
+    /// robot back where an earlier one was.
+    pub fn spawn_network_at(protocol: NetProtocol, address: &str, config: EmulatorConfig) -> io::Result<Self> {
+        let io = match protocol {
+            NetProtocol::Tcp => {
+                let listener = TcpListener::bind(address)?;
+                listener.set_nonblocking(true)?;
+                RobotIo::Tcp { listener, stream: None }
+            },
+            NetProtocol::Udp => {
+                let socket = UdpSocket::bind(address)?;
+                socket.set_read_timeout(Some(POLL_TIMEOUT))?;
+                RobotIo::Udp { socket, peer: None }
+            },
+        };
+        let path = protocol.endpoint(&io.local_addr()?.to_string());
+        Ok(Self::start(path, io, config))
+    }
+
+    fn start(path: String, io: RobotIo, config: EmulatorConfig) -> Self {
+        let shared = Arc::new(Shared {
+            stop: AtomicBool::new(false),
+            faults: Mutex::new(config.faults),
+            snapshot: Mutex::new(EmulatorSnapshot {
+                state: DriverState::Disabled,
+                last_drive: None,
+                drives_received: 0,
+                moving_drives_received: 0,
+                frames_received: 0,
+                frames_corrupt: 0,
+                firmware: None,
+                reboots: 0,
+                saved_params: None,
+            }),
+        });
+        let mut robot = Robot {
+            io,
+            config,
+            shared: shared.clone(),
+            decoder: FrameDecoder::new(),
+            seq: 0,
+            state: DriverState::Disabled,
+            bootloader: false,
+            flash: Vec::new(),
+            rng: Rng::seeded(),
+            start: Instant::now(),
+            encoders: Vec::new(),
+            heading_deg: 0.0,
+        };
+        let thread = thread::spawn(move || robot.run());
+        Self { path, shared, thread: Some(thread) }
+    }
+
+    /// Device path or network endpoint the ground station should open.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn set_faults(&self, faults: FaultConfig) {
+        *self.shared.faults.lock().unwrap() = faults;
+    }
+
+    pub fn snapshot(&self) -> EmulatorSnapshot {
+        self.shared.snapshot.lock().unwrap().clone()
+    }
+}
+
+impl Drop for RobotEmulator {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The robot's end of whichever transport it was spawned with.
+enum RobotIo {
+    Pty { master: TTYPort, _slave: TTYPort },
+    /// Serves one ground connection at a time, accepting the next once it closes.
+    Tcp { listener: TcpListener, stream: Option<TcpStream> },
+    /// Answers whoever sent the last datagram.
+    Udp { socket: UdpSocket, peer: Option<SocketAddr> },
+}
+
+impl RobotIo {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            RobotIo::Pty { .. } => Err(io::Error::new(io::ErrorKind::Unsupported, "pseudo-terminal has no address")),
+            RobotIo::Tcp { listener, .. } => listener.local_addr(),
+            RobotIo::Udp { socket, .. } => socket.local_addr(),
+        }
+    }
+
+    /// Reads with a short timeout, reporting `TimedOut` while nothing arrived.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            RobotIo::Pty { master, .. } => master.read(buf),
+            RobotIo::Tcp { listener, stream } => {
+                let Some(connection) = stream else {
+                    let (connection, _) = listener.accept()?;
+                    connection.set_nonblocking(false)?;
+                    connection.set_read_timeout(Some(POLL_TIMEOUT))?;
+                    *stream = Some(connection);
+                    return Err(io::ErrorKind::TimedOut.into());
+                };
+                match connection.read(buf) {
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => Err(io::ErrorKind::TimedOut.into()),
+                    Ok(count) if count > 0 => Ok(count),
+                    // Closed or reset: wait for the ground station to reconnect.
+                    _ => {
+                        *stream = None;
+                        Err(io::ErrorKind::TimedOut.into())
+                    },
+                }
+            },
+            RobotIo::Udp { socket, peer } => match socket.recv_from(buf) {
+                Ok((count, from)) => {
+                    *peer = Some(from);
+                    Ok(count)
+                },
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => Err(io::ErrorKind::TimedOut.into()),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// Sends a frame, dropping it while no ground station is connected.
+    fn write_all(&mut self, frame: &[u8]) {
+        match self {
+            RobotIo::Pty { master, .. } => {
+                let _ = master.write_all(frame);
+            },
+            RobotIo::Tcp { stream, .. } => {
+                if let Some(connection) = stream {
+                    let _ = connection.write_all(frame);
+                }
+            },
+            RobotIo::Udp { socket, peer } => {
+                if let Some(peer) = peer {
+                    let _ = socket.send_to(frame, *peer);
+                }
+            },
+        }
+    }
+}
+
+struct Robot {
+    io: RobotIo,
+    config: EmulatorConfig,
+    shared: Arc<Shared>,
+    decoder: FrameDecoder,
+    seq: u16,
+    state: DriverState,
+    bootloader: bool,
+    flash: Vec<u8>,
+    rng: Rng,
+    start: Instant,
+    /// Accumulated ticks per motor, kept fractional between telemetry frames.
+    encoders: Vec<f32>,
+    /// Integrated from the wheel speeds, as a gyro would report it.
+    heading_deg: f32,
+}
+
+impl Robot {
+    fn run(&mut self) {
+        let mut buf = [0u8; 256];
+        let mut last_heartbeat = Instant::now();
+        let mut last_telemetry = Instant::now();
+        while !self.shared.stop.load(Ordering::Relaxed) {
+            match self.io.read(&mut buf) {
+                Ok(count) => {
+                    for result in self.decoder.decode(&buf[..count]) {
+                        match result {
+                            Ok(packet) => self.handle(packet),
+                            Err(_) => self.shared.snapshot.lock().unwrap().frames_corrupt += 1,
+                        }
+                    }
+                },
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => (),
+                // The master reports EIO while no one has the slave open, and the
+                // listener `WouldBlock` while no one has connected.
+                Err(_) => thread::sleep(POLL_TIMEOUT),
+            }
+            if last_heartbeat.elapsed() >= self.config.heartbeat_period {
+                last_heartbeat = Instant::now();
+                self.send(Message::Heartbeat(self.uptime_ms()));
+            }
+            if last_telemetry.elapsed() >= self.config.telemetry_period {
+                let elapsed = last_telemetry.elapsed();
+                last_telemetry = Instant::now();
+                let robot_us = self.clock_us();
+                let telemetry = self.telemetry(elapsed);
+                self.send(Message::Telemetry { robot_us, telemetry });
+            }
+        }
+    }
+
+    fn handle(&mut self, packet: Packet) {
+        self.shared.snapshot.lock().unwrap().frames_received += 1;
+        match packet.message {
+            Message::Hello => self.send(Message::Identity(self.config.identity.clone())),
+            Message::Heartbeat(stamp) => self.send(Message::HeartbeatAck(stamp)),
+            Message::TimeRequest { ground_us } => {
+                let robot_rx_us = self.clock_us();
+                self.send(Message::TimeReply { ground_us, robot_rx_us, robot_tx_us: self.clock_us() });
+            },
+            Message::StateCommand(to) => {
+                if self.state == DriverState::EStopped && !matches!(to, DriverState::Disabled | DriverState::EStopped) {
+                    self.send(Message::Nack { ack_seq: packet.seq, code: NACK_LATCHED });
+                    self.log(LogLevel::Warn, "state", format!("refused {} while e-stopped", to));
+                } else {
+                    self.log(LogLevel::Info, "state", format!("{} -> {}", self.state, to));
+                    self.state = to;
+                    self.shared.snapshot.lock().unwrap().state = to;
+                    self.send(Message::StateAck { ack_seq: packet.seq, state: to });
+                }
+            },
+            Message::Drive(command) => {
+                let mut snapshot = self.shared.snapshot.lock().unwrap();
+                snapshot.last_drive = Some(command);
+                snapshot.drives_received += 1;
+                if command != DriveCommand::neutral() {
+                    snapshot.moving_drives_received += 1;
+                }
+            },
+            Message::BootEnter { base_address: Some(base_address) } if base_address != self.config.app_base => {
+                self.send(Message::Nack { ack_seq: packet.seq, code: NACK_WRONG_BASE });
+            },
+            Message::BootEnter { .. } => {
+                self.bootloader = true;
+                self.flash.clear();
+                self.send(Message::BootReady { max_chunk: self.config.boot_max_chunk });
+            },
+            Message::FlashWrite { .. } | Message::FlashVerify { .. } if !self.bootloader => {
+                self.send(Message::Nack { ack_seq: packet.seq, code: NACK_NOT_IN_BOOTLOADER });
+            },
+            Message::FlashWrite { offset, data } => {
+                let start = offset as usize;
+                let end = start + data.len();
+                if end > FLASH_SIZE || data.len() > self.config.boot_max_chunk as usize {
+                    self.send(Message::Nack { ack_seq: packet.seq, code: NACK_FLASH_RANGE });
+                    return;
+                }
+                if self.flash.len() < end {
+                    self.flash.resize(end, 0xFF);
+                }
+                self.flash[start..end].copy_from_slice(&data);
+                self.send(Message::FlashAck { offset });
+            },
+            Message::FlashVerify { length, .. } => {
+                let length = (length as usize).min(self.flash.len());
+                self.flash.truncate(length);
+                self.send(Message::FlashVerified { crc: crc32(&self.flash) });
+            },
+            Message::ParamList => {
+                let count = self.config.params.len() as u16;
+                for info in self.config.params.clone() {
+                    self.send(Message::ParamInfo { count, info });
+                }
+            },
+            Message::ParamGet { index } => match self.config.params.get(index as usize) {
+                Some(info) => self.send(Message::ParamValue { ack_seq: packet.seq, index, value: info.value }),
+                None => self.send(Message::Nack { ack_seq: packet.seq, code: NACK_PARAM_UNKNOWN }),
+            },
+            Message::ParamSet { index, value } => match self.config.params.get_mut(index as usize) {
+                Some(info) if info.value.kind() == value.kind() && info.in_range(&value) => {
+                    info.value = value;
+                    self.send(Message::ParamValue { ack_seq: packet.seq, index, value });
+                },
+                Some(info) => {
+                    let text = format!("{} rejected {}", info.name, value);
+                    self.send(Message::Nack { ack_seq: packet.seq, code: NACK_PARAM_RANGE });
+                    self.log(LogLevel::Warn, "params", text);
+                },
+                None => self.send(Message::Nack { ack_seq: packet.seq, code: NACK_PARAM_UNKNOWN }),
+            },
+            Message::ParamSave => {
+                let values = self.config.params.iter().map(|info| info.value).collect();
+                self.shared.snapshot.lock().unwrap().saved_params = Some(values);
+                self.send(Message::ParamSaved { ack_seq: packet.seq });
+                self.log(LogLevel::Info, "params", "saved to flash".to_string());
+            },
+            Message::Reboot if self.bootloader => {
+                self.bootloader = false;
+                let mut snapshot = self.shared.snapshot.lock().unwrap();
+                snapshot.firmware = Some((self.flash.len(), crc32(&self.flash)));
+                snapshot.reboots += 1;
+            },
+            _ => (),
+        }
+    }
+
+    /// Moves the motors as commanded since the last frame and reports how they are doing.
+    fn telemetry(&mut self, elapsed: Duration) -> RobotTelemetry {
+        let drive = match self.state {
+            DriverState::Enabled => self.shared.snapshot.lock().unwrap().last_drive.unwrap_or_default(),
+            _ => DriveCommand::neutral(),
+        };
+        self.encoders.resize(self.config.motors, 0.0);
+        let left = (drive.wheels[DriveCommand::FRONT_LEFT] + drive.wheels[DriveCommand::REAR_LEFT]) / 2.0;
+        let right = (drive.wheels[DriveCommand::FRONT_RIGHT] + drive.wheels[DriveCommand::REAR_RIGHT]) / 2.0;
+        let turned = (left - right) / 2.0 * TURN_DEG_PER_SECOND * elapsed.as_secs_f32();
+        self.heading_deg = (self.heading_deg + turned + 180.0).rem_euclid(360.0) - 180.0;
+        let mut total_current = 0.0;
+        let motors = self.encoders.iter_mut().enumerate().map(|(index, ticks)| {
+            let command = drive.wheels[index % drive.wheels.len()];
+            *ticks += command * TICKS_PER_SECOND * elapsed.as_secs_f32();
+            let current_a = 0.3 + 4.0 * command.abs();
+            total_current += current_a;
+            MotorTelemetry { current_a, encoder_ticks: *ticks as i32, temperature_c: 30.0 + 10.0 * command.abs() }
+        }).collect();
+        RobotTelemetry {
+            // Sags under load and drains a little every minute.
+            battery_v: FULL_BATTERY_V - 0.05 * total_current - self.start.elapsed().as_secs_f32() / 600.0,
+            mcu_temperature_c: 38.5,
+            faults: self.shared.faults.lock().unwrap().raised,
+            motors,
+            heading_deg: Some(self.heading_deg),
+        }
+    }
+
+    fn log(&mut self, level: LogLevel, tag: &str, text: String) {
+        let robot_us = self.clock_us();
+        self.send(Message::Log { robot_us, level, tag: tag.to_string(), text });
+    }
+
+    fn send(&mut self, message: Message) {
+        let faults = *self.shared.faults.lock().unwrap();
+        if faults.silent {
+            return;
+        }
+        let packet = Packet::new(self.seq, message);
+        self.seq = self.seq.wrapping_add(1);
+        let mut frame = packet.encode();
+        if self.rng.chance(faults.corrupt_frame_rate) && frame.len() > 1 {
+            let index = self.rng.below(frame.len() - 1);
+            frame[index] ^= 1 << self.rng.below(8);
+        }
+        if faults.drop_byte_rate > 0.0 {
+            frame.retain(|_| !self.rng.chance(faults.drop_byte_rate));
+        }
+        if !faults.latency.is_zero() {
+            thread::sleep(faults.latency);
+        }
+        self.io.write_all(&frame);
+    }
+
+    fn uptime_ms(&self) -> u32 {
+        self.start.elapsed().as_millis() as u32
+    }
+
+    /// The robot's free-running microsecond clock, which starts at boot and drifts.
+    fn clock_us(&self) -> u64 {
+        (self.start.elapsed().as_micros() as f64 * (1.0 + self.config.clock_drift_ppm / 1e6)) as u64
+    }
+}
+
+/// Small xorshift generator so fault injection needs no extra dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn seeded() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+        Self(nanos | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        let sample = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        probability > 0.0 && sample < probability
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
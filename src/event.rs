@@ -1,10 +1,10 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use crossterm::event::{Event as CrosstermEvent, KeyEvent, MouseEvent};
 use futures::{FutureExt, StreamExt};
 use tokio::sync::mpsc;
 use gilrs::EventType as GamepadEventType;
-use crate::{app::AppResult, tasks::DriverEvent};
+use crate::{app::AppResult, tasks::{ControllerStatus, DriverEvent, FirmwareError, FirmwareImage, ProbeResult}};
 
 /// Terminal events.
 #[derive(Debug)]
@@ -17,10 +17,18 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
-    /// Controller Event
-    Controller(GamepadEventType),
-    /// Driver Event
-    Driver(DriverEvent),
+    /// Controller Event, with the time gilrs saw it
+    Controller(GamepadEventType, SystemTime),
+    /// Names of the controllers plugged in, answering a list request
+    Controllers(Vec<String>),
+    /// The selected controller was found, lost or is being waited for
+    ControllerStatus(ControllerStatus),
+    /// Driver Event, tagged with the name of the driver that sent it
+    Driver(String, DriverEvent),
+    /// Robot scan results, one per probed port
+    Scan(Vec<ProbeResult>),
+    /// A firmware image read off the UI thread, or why it couldn't be
+    Firmware(Result<FirmwareImage, FirmwareError>),
 }
 
 /// Terminal event handler.
@@ -57,9 +65,8 @@ impl EventHandler {
                   Some(Ok(evt)) = crossterm_event => {
                     match evt {
                       CrosstermEvent::Key(key) => {
-                        if key.kind == crossterm::event::KeyEventKind::Press {
-                          _sender.send(Event::Key(key)).unwrap();
-                        }
+                        // Releases only arrive where the terminal reports them; keyboard driving uses them.
+                        _sender.send(Event::Key(key)).unwrap();
                       },
                       CrosstermEvent::Mouse(mouse) => {
                         _sender.send(Event::Mouse(mouse)).unwrap();
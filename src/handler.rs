@@ -1,8 +1,43 @@
 use crate::app::{App, AppResult};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::teleop::KeyboardDrive;
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    // Releases only matter while driving.
+    if key_event.kind == KeyEventKind::Release && !app.is_keyboard_driving() {
+        return Ok(());
+    }
+    // Auto-repeat is only wanted for typing into a prompt: a held Enter must not select
+    // over and over, and drive keys stay held until their release arrives.
+    let typing = app.is_prompting() && matches!(key_event.code, KeyCode::Char(_) | KeyCode::Backspace);
+    if key_event.kind == KeyEventKind::Repeat && !typing {
+        return Ok(());
+    }
+    // An open prompt takes every key but `Ctrl-C`.
+    if app.is_prompting() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => app.quit(),
+            KeyCode::Char(c) => app.prompt_input(c),
+            KeyCode::Backspace => app.prompt_backspace(),
+            KeyCode::Enter => app.prompt_submit(),
+            KeyCode::Esc => app.prompt_cancel(),
+            _ => {}
+        }
+        return Ok(());
+    }
+    if app.is_keyboard_driving() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => app.quit(),
+            // Back to the menus on `Tab` or `ESC`, rather than quitting mid-drive
+            KeyCode::Tab | KeyCode::Esc if key_event.kind == KeyEventKind::Press => app.toggle_keyboard_drive(),
+            KeyCode::Char(' ') if key_event.kind == KeyEventKind::Press => app.estop(),
+            KeyCode::Char(c) if KeyboardDrive::is_drive_key(c) => app.keyboard_drive_key(c, key_event.kind == KeyEventKind::Press),
+            _ => {}
+        }
+        return Ok(());
+    }
     match key_event.code {
         // Exit application on `ESC` or `q`
         KeyCode::Esc | KeyCode::Char('q') => {
@@ -14,6 +49,14 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
                 app.quit();
             }
         }
+        // E-stop the robot on `Space`
+        KeyCode::Char(' ') => {
+            app.estop();
+        }
+        // Drive from the keyboard on `Tab`
+        KeyCode::Tab => {
+            app.toggle_keyboard_drive();
+        }
         // Counter handlers
         KeyCode::Right => {
             app.control_panel_next_window();
@@ -0,0 +1,11 @@
+pub mod app;
+pub mod emulator;
+pub mod event;
+pub mod handler;
+pub mod pages;
+pub mod tui;
+pub mod ui;
+pub mod page_functions;
+pub mod protocol;
+pub mod tasks;
+pub mod teleop;
@@ -2,22 +2,13 @@ use std::io;
 
 use ratatui::{backend::CrosstermBackend, Terminal};
 
-use crate::{
+use nightmare_gs::{
     app::{App, AppResult},
     event::{Event, EventHandler},
     handler::handle_key_events,
     tui::Tui,
 };
 
-pub mod app;
-pub mod event;
-pub mod handler;
-pub mod pages;
-pub mod tui;
-pub mod ui;
-pub mod page_functions;
-pub mod tasks;
-
 #[tokio::main]
 async fn main() -> AppResult<()> {
     // Create an application.
@@ -26,10 +17,11 @@ async fn main() -> AppResult<()> {
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stdout());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(250);
+    let events = EventHandler::new(50);
     app.add_sender(events.get_sender());
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
+    app.set_key_releases(tui.reports_key_releases());
 
     // Start the main loop.
     while app.is_running() {
@@ -43,12 +35,16 @@ async fn main() -> AppResult<()> {
             Event::Resize(x, y) => {
                 app.set_x_y(x, y);
             }
-            Event::Controller(event) => {
-                 app.handle_controller_event(event);
+            Event::Controller(event, time) => {
+                 app.handle_controller_event(event, time);
             },
-            Event::Driver(event) => {
-                app.handle_driver_event(event)
+            Event::Controllers(names) => app.handle_controllers(names),
+            Event::ControllerStatus(status) => app.handle_controller_status(status),
+            Event::Driver(name, event) => {
+                app.handle_driver_event(name, event)
             }
+            Event::Scan(results) => app.handle_scan(results),
+            Event::Firmware(image) => app.handle_firmware(image),
         }
     }
 
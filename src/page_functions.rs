@@ -1,5 +1,29 @@
 mod stick;
+mod bindings;
+mod console;
+mod discovery;
+mod drive_mode;
+mod drivers;
+mod firmware;
+mod keyboard;
+mod parameters;
 mod page;
+mod port;
+mod robotlog;
+mod state;
+mod tuning;
 
 pub use stick::*;
-pub use page::*;
\ No newline at end of file
+pub use bindings::*;
+pub use console::*;
+pub use discovery::*;
+pub use drive_mode::*;
+pub use drivers::*;
+pub use firmware::*;
+pub use keyboard::*;
+pub use parameters::*;
+pub use page::*;
+pub use port::*;
+pub use robotlog::*;
+pub use state::*;
+pub use tuning::*;
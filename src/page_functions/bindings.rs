@@ -0,0 +1,38 @@
+use std::str::FromStr;
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    pages::{Config, ConfigFnOptions, ControlResult, Window},
+    teleop::{Action, BindingAction, Profile},
+};
+
+/// The Bindings menu before any pad has connected.
+pub fn bindings_window() -> (Window, Option<ControlResult>) {
+    (Window::new("Bindings".to_string()).with_configs(binding_configs(&Profile::default(), "default")), None)
+}
+
+/// Fixed actions followed by one entry per action showing its input; picking one
+/// rebinds it to whatever is moved or pressed next.
+pub fn binding_configs(profile: &Profile, profile_name: &str) -> Vec<Config> {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::None(on_select));
+    let mut configs = vec![
+        Config::new(format!("Profile: {}", profile_name)),
+        entry("Save Bindings", || binding(BindingAction::Save)),
+        entry("Reload Bindings", || binding(BindingAction::Reload)),
+        entry("Cancel Capture", || binding(BindingAction::CancelCapture)),
+    ];
+    configs.extend(Action::iter().map(|action| {
+        let input = profile.input(action).map_or("unbound".to_string(), |input| input.to_string());
+        Config::new(format!("{}: {}", action, input))
+            .with_value(action.to_string())
+            .with_on_select(ConfigFnOptions::ConfigToNone(|config|
+                binding(BindingAction::Capture(Action::from_str(config.get_value()).ok()?))))
+    }));
+    configs
+}
+
+fn binding(action: BindingAction) -> Option<ControlResult> {
+    Some(ControlResult::Binding(action))
+}
@@ -0,0 +1,19 @@
+use crate::pages::{Config, ConfigFnOptions, ConsoleAction, ControlResult, Page, PromptPurpose, SendFormat, Window};
+
+pub fn serial_console_window() -> (Window, Option<ControlResult>) {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::None(on_select));
+    (Window::new("Serial Console".to_string()).with_configs(vec![
+        entry("Show Console", || Some(ControlResult::ChangePage(Page::SerialConsole))),
+        entry("Send Text", || send(SendFormat::Text)),
+        entry("Send Hex", || send(SendFormat::Hex)),
+        entry("Send Escaped", || send(SendFormat::Escaped)),
+        entry("Pause/Resume", || Some(ControlResult::Console(ConsoleAction::TogglePause))),
+        entry("Clear", || Some(ControlResult::Console(ConsoleAction::Clear))),
+    ]),
+    None)
+}
+
+fn send(format: SendFormat) -> Option<ControlResult> {
+    Some(ControlResult::OpenPrompt(PromptPurpose::ConsoleSend(format)))
+}
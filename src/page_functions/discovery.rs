@@ -0,0 +1,24 @@
+use crate::{
+    pages::{Config, ConfigFnOptions, ControlResult, Window},
+    tasks::{DriverEvent, ProbeResult},
+};
+
+/// Starts a scan; the window is filled in when the results come back.
+pub fn scan_window() -> (Window, Option<ControlResult>) {
+    (Window::new("Scan for Robots".to_string()).with_configs(vec![Config::new("Scanning...".to_string())]),
+    Some(ControlResult::ScanForRobots))
+}
+
+/// A rescan entry followed by every port that answered; picking one connects its driver.
+pub fn scan_configs(results: &[ProbeResult]) -> Vec<Config> {
+    let mut configs = vec![Config::new("Scan Again".to_string())
+        .with_on_select(ConfigFnOptions::None(|| Some(ControlResult::ScanForRobots)))];
+    configs.extend(results.iter().filter_map(|result| {
+        let identity = result.outcome.as_ref().ok()?;
+        Some(Config::new(format!("{} {} on {}", identity.name, identity.firmware, result.port))
+            .with_value(result.port.clone())
+            .with_on_select(ConfigFnOptions::ConfigToNone(|config|
+                Some(ControlResult::DriverChange(DriverEvent::SetPort(config.get_value().to_string()))))))
+    }));
+    configs
+}
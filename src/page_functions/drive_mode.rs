@@ -0,0 +1,14 @@
+use strum::IntoEnumIterator;
+
+use crate::{pages::{Config, ConfigFnOptions, ControlResult, Window}, teleop::DriveMode};
+
+pub fn drive_mode_window() -> (Window, Option<ControlResult>) {
+    let mut configs: Vec<Config> = DriveMode::iter().map(|mode| Config::new(mode.to_string())
+        .with_on_select(ConfigFnOptions::ConfigToNone(|config| {
+            Some(ControlResult::DriveMode(config.get_short_text().parse().ok()?))
+        })))
+        .collect();
+    configs.push(Config::new("Toggle Field Oriented".to_string())
+        .with_on_select(ConfigFnOptions::None(|| Some(ControlResult::ToggleFieldOriented))));
+    (Window::new("Drive Mode".to_string()).with_configs(configs), None)
+}
@@ -0,0 +1,29 @@
+use crate::{
+    pages::{Config, ConfigFnOptions, ControlResult, Window},
+    tasks::DriverPort,
+};
+
+/// Lists the serial ports; each opens a window to start, stop or activate its driver.
+pub fn drivers_window() -> (Window, Option<ControlResult>) {
+    let ports = DriverPort::get_ports().unwrap_or_default();
+    (Window::new("Drivers".to_string()).with_configs(driver_configs(ports)), None)
+}
+
+/// One entry per serial port or network endpoint.
+pub fn driver_configs(ports: Vec<String>) -> Vec<Config> {
+    ports.into_iter()
+        .map(|port| Config::new(port).with_on_select(ConfigFnOptions::ConfigToWindow(driver_window)))
+        .collect()
+}
+
+/// Window titled with the port, so its entries know which driver they act on.
+fn driver_window(config: &Config) -> (Window, Option<ControlResult>) {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::WindowToNone(on_select));
+    (Window::new(config.get_short_text().to_string()).with_configs(vec![
+        entry("Start", |window| Some(ControlResult::StartDriver(window.get_name().to_string()))),
+        entry("Stop", |window| Some(ControlResult::StopDriver(window.get_name().to_string()))),
+        entry("Make Active", |window| Some(ControlResult::SelectDriver(window.get_name().to_string()))),
+    ]),
+    None)
+}
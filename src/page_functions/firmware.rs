@@ -0,0 +1,36 @@
+use std::{fs, path::Path};
+
+use crate::pages::{Config, ConfigFnOptions, ControlResult, PromptPurpose, Window};
+
+/// Directories searched for images, relative to where the ground station was started.
+const FIRMWARE_DIRS: [&str; 2] = ["firmware", "."];
+const FIRMWARE_EXTENSIONS: [&str; 3] = ["bin", "hex", "ihex"];
+
+/// Offers the images found next to the ground station, plus a typed path.
+pub fn firmware_window() -> (Window, Option<ControlResult>) {
+    let mut configs = vec![Config::new("Enter Path".to_string())
+        .with_on_select(ConfigFnOptions::None(|| Some(ControlResult::OpenPrompt(PromptPurpose::FirmwarePath))))];
+    configs.extend(find_images().into_iter().map(|path|
+        Config::new(path).with_on_select(ConfigFnOptions::ConfigToNone(|config|
+            Some(ControlResult::UploadFirmware(config.get_short_text().to_string()))))));
+    (Window::new("Upload Firmware".to_string()).with_configs(configs), None)
+}
+
+fn find_images() -> Vec<String> {
+    let mut images: Vec<String> = FIRMWARE_DIRS.iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_image(path))
+        .map(|path| path.display().to_string())
+        .collect();
+    images.sort();
+    images
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| FIRMWARE_EXTENSIONS.iter().any(|known| extension.eq_ignore_ascii_case(known)))
+}
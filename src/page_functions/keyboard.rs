@@ -0,0 +1,11 @@
+use crate::pages::{Config, ConfigFnOptions, ControlResult, PromptPurpose, Window};
+
+pub fn keyboard_window() -> (Window, Option<ControlResult>) {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::None(on_select));
+    (Window::new("Keyboard Drive".to_string()).with_configs(vec![
+        entry("Toggle Driving (Tab)", || Some(ControlResult::ToggleKeyboardDrive)),
+        entry("Ramp Rate ([ and ])", || Some(ControlResult::OpenPrompt(PromptPurpose::KeyboardRamp))),
+    ]),
+    None)
+}
@@ -0,0 +1,33 @@
+use crate::pages::{Config, ConfigFnOptions, ControlResult, Page, ParamAction, PromptPurpose, Window};
+
+/// The Parameters menu before the robot has listed anything.
+pub fn parameters_window() -> (Window, Option<ControlResult>) {
+    (Window::new("Parameters".to_string()).with_configs(parameter_configs(Vec::new())), None)
+}
+
+/// Fixed actions followed by one entry per parameter name.
+pub fn parameter_configs(names: Vec<String>) -> Vec<Config> {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::None(on_select));
+    let mut configs = vec![
+        entry("Show Parameters", || Some(ControlResult::ChangePage(Page::Parameters))),
+        entry("Refresh", || Some(ControlResult::Param(ParamAction::Refresh))),
+        entry("Save to Flash", || Some(ControlResult::Param(ParamAction::Save))),
+    ];
+    configs.extend(names.into_iter().map(|name|
+        Config::new(name).with_on_select(ConfigFnOptions::ConfigToWindow(parameter_window))));
+    configs
+}
+
+/// Edits for one parameter; the window is titled with its name so entries know which.
+fn parameter_window(config: &Config) -> (Window, Option<ControlResult>) {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::WindowToNone(on_select));
+    (Window::new(config.get_short_text().to_string()).with_configs(vec![
+        entry("Enter Value", |window| Some(ControlResult::OpenPrompt(PromptPurpose::ParamValue(window.get_name().to_string())))),
+        entry("Toggle", |window| Some(ControlResult::Param(ParamAction::Toggle(window.get_name().to_string())))),
+        entry("Reset to Default", |window| Some(ControlResult::Param(ParamAction::ResetDefault(window.get_name().to_string())))),
+        entry("Read Back", |window| Some(ControlResult::Param(ParamAction::Read(window.get_name().to_string())))),
+    ]),
+    None)
+}
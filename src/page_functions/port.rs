@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+use serialport::{DataBits, FlowControl, Parity, StopBits};
+
+use crate::{
+    pages::{Config, ConfigFnOptions, ConfigOption, ControlResult, PromptPurpose, Window},
+    tasks::{DriverEvent, DriverTask, LineSetting, LineSettings, NetProtocol},
+};
+
+const BAUD_RATES: [u32; 9] = [9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600, 1000000];
+
+pub fn port_control_window() -> (Window, Option<ControlResult>) {
+    (Window::new("Driver Port Control".to_string()).with_configs(port_control_configs(&LineSettings::default())), None)
+}
+
+/// The port controls, each line setting entry carrying the active driver's current
+/// value for its window to tick.
+pub fn port_control_configs(line: &LineSettings) -> Vec<Config> {
+    let prompt = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::None(on_select));
+    let setting = |name: &str, value: String, window| Config::new(name.to_string())
+        .with_value(value)
+        .with_on_select(ConfigFnOptions::ConfigToWindow(window));
+    let parity = match line.parity {
+        Parity::None => "None",
+        Parity::Odd => "Odd",
+        Parity::Even => "Even",
+    };
+    vec![
+        Config::new("Select Port".to_string()).with_on_select(ConfigFnOptions::NoneToWindow(DriverTask::list_ports)),
+        prompt("Connect TCP", || Some(ControlResult::OpenPrompt(PromptPurpose::NetworkAddress(NetProtocol::Tcp)))),
+        prompt("Connect UDP", || Some(ControlResult::OpenPrompt(PromptPurpose::NetworkAddress(NetProtocol::Udp)))),
+        setting("Baud Rate", line.baud.to_string(), baud_window),
+        setting("Data Bits", u8::from(line.data_bits).to_string(), data_bits_window),
+        setting("Parity", parity.to_string(), parity_window),
+        setting("Stop Bits", u8::from(line.stop_bits).to_string(), stop_bits_window),
+        setting("Flow Control", line.flow_control.to_string(), flow_control_window),
+    ]
+}
+
+/// One line setting's choices, titled with and ticking the current value, which
+/// `config` carries.
+fn value_window(config: &Config, values: Vec<String>, on_select: fn(&Config) -> Option<ControlResult>) -> (Window, Option<ControlResult>) {
+    let current = config.get_value();
+    (Window::new(format!("{}: {}", config.get_short_text(), current)).with_configs(values
+        .into_iter()
+        .map(|value| {
+            let checked = value == current;
+            Config::new(value)
+                .with_configoption(ConfigOption::CheckBox(checked))
+                .with_on_select(ConfigFnOptions::ConfigToNone(on_select))
+        })
+        .collect()),
+    None)
+}
+
+fn line_change(setting: LineSetting) -> Option<ControlResult> {
+    Some(ControlResult::DriverChange(DriverEvent::SetLine(setting)))
+}
+
+fn baud_window(config: &Config) -> (Window, Option<ControlResult>) {
+    value_window(config, BAUD_RATES.iter().map(|baud| baud.to_string()).collect(), |config|
+        config.get_short_text().parse().ok().and_then(|baud| line_change(LineSetting::Baud(baud))))
+}
+
+fn data_bits_window(config: &Config) -> (Window, Option<ControlResult>) {
+    value_window(config, ["5", "6", "7", "8"].map(String::from).to_vec(), |config| {
+        let bits: u8 = config.get_short_text().parse().ok()?;
+        line_change(LineSetting::DataBits(DataBits::try_from(bits).ok()?))
+    })
+}
+
+fn parity_window(config: &Config) -> (Window, Option<ControlResult>) {
+    value_window(config, ["None", "Odd", "Even"].map(String::from).to_vec(), |config| {
+        let parity = match config.get_short_text() {
+            "Odd" => Parity::Odd,
+            "Even" => Parity::Even,
+            _ => Parity::None,
+        };
+        line_change(LineSetting::Parity(parity))
+    })
+}
+
+fn stop_bits_window(config: &Config) -> (Window, Option<ControlResult>) {
+    value_window(config, ["1", "2"].map(String::from).to_vec(), |config| {
+        let bits: u8 = config.get_short_text().parse().ok()?;
+        line_change(LineSetting::StopBits(StopBits::try_from(bits).ok()?))
+    })
+}
+
+fn flow_control_window(config: &Config) -> (Window, Option<ControlResult>) {
+    value_window(config, ["None", "Software", "Hardware"].map(String::from).to_vec(), |config|
+        line_change(LineSetting::FlowControl(FlowControl::from_str(config.get_short_text()).ok()?)))
+}
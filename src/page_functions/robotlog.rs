@@ -0,0 +1,25 @@
+use crate::{pages::{Config, ConfigFnOptions, ControlResult, LogAction, Page, PromptPurpose, Window}, protocol::LogLevel};
+
+pub fn robot_log_window() -> (Window, Option<ControlResult>) {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::None(on_select));
+    (Window::new("Robot Log".to_string()).with_configs(vec![
+        entry("Show Log", || Some(ControlResult::ChangePage(Page::RobotLog))),
+        entry("Search", || Some(ControlResult::OpenPrompt(PromptPurpose::LogSearch))),
+        entry("Clear Search", || log(LogAction::ClearSearch)),
+        entry("Errors Only", || log(LogAction::MinLevel(LogLevel::Error))),
+        entry("Warnings and Up", || log(LogAction::MinLevel(LogLevel::Warn))),
+        entry("Info and Up", || log(LogAction::MinLevel(LogLevel::Info))),
+        entry("Debug and Up", || log(LogAction::MinLevel(LogLevel::Debug))),
+        entry("Everything", || log(LogAction::MinLevel(LogLevel::Trace))),
+        entry("Scroll Up", || log(LogAction::ScrollUp)),
+        entry("Scroll Down", || log(LogAction::ScrollDown)),
+        entry("Follow Newest", || log(LogAction::ScrollToEnd)),
+        entry("Clear", || log(LogAction::Clear)),
+    ]),
+    None)
+}
+
+fn log(action: LogAction) -> Option<ControlResult> {
+    Some(ControlResult::Log(action))
+}
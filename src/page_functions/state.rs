@@ -0,0 +1,34 @@
+use crate::{
+    pages::{Config, ConfigFnOptions, ConfigOption, ControlResult, Window},
+    tasks::{DriverEvent, DriverState},
+};
+
+pub fn driver_state_window() -> (Window, Option<ControlResult>) {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::None(on_select));
+    (Window::new("Driver State".to_string()).with_configs(vec![
+        entry("Disable", || request(DriverState::Disabled)),
+        entry("Enable", || request(DriverState::Enabled)),
+        entry("E-Stop", || request(DriverState::EStopped)),
+        entry("Reset Latch", || Some(ControlResult::DriverChange(DriverEvent::Reset))),
+    ]),
+    None)
+}
+
+fn request(state: DriverState) -> Option<ControlResult> {
+    Some(ControlResult::DriverChange(DriverEvent::StateChange(state)))
+}
+
+const DRIVE_RATES_HZ: [u32; 5] = [10, 20, 50, 100, 200];
+
+pub fn drive_rate_window() -> (Window, Option<ControlResult>) {
+    (Window::new("Drive Rate (Hz)".to_string()).with_configs(DRIVE_RATES_HZ
+        .iter()
+        .map(|rate| Config::new(rate.to_string())
+            .with_configoption(ConfigOption::CheckBox(false))
+            .with_on_select(ConfigFnOptions::ConfigToNone(|config|
+                config.get_short_text().parse().ok()
+                    .map(|rate| ControlResult::DriverChange(DriverEvent::SetDriveRate(rate))))))
+        .collect()),
+    None)
+}
@@ -1,26 +1,22 @@
-use std::task::Poll::{self, Pending, Ready};
-use std::future::*;
-use futures::{poll, FutureExt};
-use gilrs::{Gamepad, Gilrs};
-
 use crate::pages::{
     Config, ConfigFnOptions, ControlResult, Window
 };
 
+/// Asks the input service for the pads plugged in; the window is filled in when it answers.
 pub fn list_controllers_window() -> (Window, Option<ControlResult>) {
-    get_controllers_to_window(false)
+    (Window::new("Controllers".to_string()).with_configs(vec![Config::new("Looking...".to_string())]),
+    Some(ControlResult::ListControllers))
 }
 
 pub fn select_controller_window() -> (Window, Option<ControlResult>){
-    get_controllers_to_window(true)
+    list_controllers_window()
 }
 
-pub fn get_controllers_to_window(select: bool) -> (Window, Option<ControlResult>) {
-    let mut gilrs = Gilrs::new().unwrap();
-    let controllers: Vec<String> = gilrs.gamepads().map(|(_, gamepad) |
-        gamepad.name().to_string()
-    ).collect();
-    (Window::new("Controllers".to_string()).with_configs(controllers
+/// A refresh entry followed by one entry per controller, selectable when `select` is set.
+pub fn controller_configs(controllers: &[String], select: bool) -> Vec<Config> {
+    let mut configs = vec![Config::new("Refresh".to_string())
+        .with_on_select(ConfigFnOptions::None(|| Some(ControlResult::ListControllers)))];
+    configs.extend(controllers
         .iter()
         .map(|s| 
             if select{
@@ -30,9 +26,8 @@ pub fn get_controllers_to_window(select: bool) -> (Window, Option<ControlResult>
                 Config::new(s.clone())
             }
             
-        )
-        .collect()),
-    None)
+        ));
+    configs
 }
 
 pub fn select_this_controller(config: &Config) -> Option<ControlResult>{
@@ -0,0 +1,40 @@
+use std::str::FromStr;
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    pages::{Config, ConfigFnOptions, ControlResult, Page, PromptPurpose, Window},
+    teleop::{Action, Stage},
+};
+
+pub fn tuning_window() -> (Window, Option<ControlResult>) {
+    let mut configs = vec![Config::new("Show Tuning".to_string())
+        .with_on_select(ConfigFnOptions::None(|| Some(ControlResult::ChangePage(Page::AxisTuning))))];
+    configs.extend(Action::iter().filter(Action::is_axis).map(|action|
+        Config::new(action.to_string()).with_on_select(ConfigFnOptions::ConfigToWindow(stage_window))));
+    (Window::new("Axis Tuning".to_string()).with_configs(configs), None)
+}
+
+/// Stages of one axis action; the window is titled with the action so entries know which.
+fn stage_window(config: &Config) -> (Window, Option<ControlResult>) {
+    let entry = |name: &str, on_select| Config::new(name.to_string())
+        .with_on_select(ConfigFnOptions::WindowToNone(on_select));
+    (Window::new(config.get_short_text().to_string()).with_configs(vec![
+        entry("Radial Deadzone (0 to <1)", |window| edit(window, Stage::Radial)),
+        entry("Deadzone (0 to <1)", |window| edit(window, Stage::Deadzone)),
+        entry("Curve (linear, expo k, cubic w)", |window| edit(window, Stage::Curve)),
+        entry("Toggle Invert", |window| Some(ControlResult::ToggleInvert(action(window)?))),
+        entry("Scale", |window| edit(window, Stage::Scale)),
+        entry("Low-pass Filter (seconds)", |window| edit(window, Stage::Filter)),
+        entry("Slew Limit (per second)", |window| edit(window, Stage::Slew)),
+    ]),
+    None)
+}
+
+fn action(window: &Window) -> Option<Action> {
+    Action::from_str(window.get_name()).ok()
+}
+
+fn edit(window: &Window, stage: Stage) -> Option<ControlResult> {
+    Some(ControlResult::OpenPrompt(PromptPurpose::Conditioning(action(window)?, stage)))
+}
@@ -2,11 +2,21 @@ mod startup;
 mod controlpanel;
 mod controllertelem;
 mod drivertelem;
+mod parameters;
+mod prompt;
+mod robotlog;
+mod axistuning;
+mod serialconsole;
 
 pub use startup::StartupPage;
 pub use controllertelem::ControllerTelem;
 pub use controlpanel::*;
 pub use drivertelem::DriverTelem;
+pub use parameters::{ParamAction, ParametersPage};
+pub use prompt::{Prompt, PromptPurpose};
+pub use robotlog::{LogAction, RobotLog};
+pub use axistuning::AxisTuning;
+pub use serialconsole::{ConsoleAction, SendFormat, SerialConsole};
 use strum_macros::{Display, EnumIter, EnumString};
 
 #[derive(Debug, Display, Clone, Copy, Default, PartialEq, EnumIter, EnumString, Eq)]
@@ -14,5 +24,9 @@ pub enum Page {
     #[default]
     Startup,
     ControllerTelem,
-    DriverTelem
+    DriverTelem,
+    SerialConsole,
+    Parameters,
+    RobotLog,
+    AxisTuning,
 }
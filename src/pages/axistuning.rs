@@ -0,0 +1,61 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph, Widget},
+};
+use strum::IntoEnumIterator;
+
+use crate::teleop::{Action, ActionMapper, Stage};
+
+/// Characters either side of centre in a value bar.
+const BAR_HALF: usize = 10;
+
+/// Raw and conditioned value of every axis action side by side, for live tuning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AxisTuning {}
+
+impl AxisTuning {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, mapper: &ActionMapper) {
+        let mut lines: Vec<Line> = Vec::new();
+        for action in Action::iter().filter(Action::is_axis) {
+            let state = mapper.state(action);
+            let conditioning = mapper.profile().conditioning(action);
+            let input = mapper.profile().input(action).map_or("unbound".to_string(), |input| input.to_string());
+            lines.push(Line::styled(format!("{} ({})", action, input), Style::default().fg(Color::Cyan)));
+            lines.push(Line::from(vec![
+                Span::raw(format!("  raw {:+.3} ", state.raw)),
+                Span::raw(bar(state.raw)),
+                Span::raw(format!("  out {:+.3} ", state.output)),
+                Span::styled(bar(state.output), Style::default().fg(if state.is_settled() { Color::Green } else { Color::Yellow })),
+            ]));
+            let stages: Vec<String> = Stage::iter().map(|stage| format!("{} {}", stage, conditioning.get(stage))).collect();
+            lines.push(Line::from(format!("  {}", stages.join(", "))));
+            lines.push(Line::default());
+        }
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title("Axis Tuning")
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
+
+/// `[----|##--]`-style bar filled from centre towards the value.
+fn bar(value: f32) -> String {
+    let filled = ((value.abs().min(1.0) * BAR_HALF as f32).round()) as usize;
+    let (left, right) = if value < 0.0 {
+        (format!("{}{}", "-".repeat(BAR_HALF - filled), "#".repeat(filled)), "-".repeat(BAR_HALF))
+    } else {
+        ("-".repeat(BAR_HALF), format!("{}{}", "#".repeat(filled), "-".repeat(BAR_HALF - filled)))
+    };
+    format!("[{}|{}]", left, right)
+}
@@ -1,62 +1,217 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Rect},
-    widgets::{Block, BorderType, Paragraph, Widget,},
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Style},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{canvas::{Canvas, Circle, Points}, Block, BorderType, Gauge, Paragraph, Widget,},
 };
 
-use gilrs::EventType as GamepadEventType;
+use std::time::SystemTime;
 
-#[derive(Debug, Clone,  Default, PartialEq, Eq)]
+use gilrs::{Axis, Button, EventType as GamepadEventType};
+
+use crate::{tasks::{ControllerStatus, TimeOfDay}, teleop::{Kinematics, PadState}};
+
+/// Seconds without an event after which the age shows yellow, then red.
+const SILENCE_LIMITS_S: (f32, f32) = (2.0, 10.0);
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ControllerTelem {
     row_index: usize,
     message: Option<String>,
+    status: Option<ControllerStatus>,
+    drive_mode: String,
+    pad: PadState,
 }
 
 impl ControllerTelem {
     pub fn new() -> Self {
-        Self { row_index: 0 , message: None}
+        Self { row_index: 0 , message: None, status: None, drive_mode: Kinematics::default().describe(), pad: PadState::new() }
     }
 
     pub fn render(&self, area: Rect, buf: &mut Buffer) {
-        let string: String;
-        if let Some(message) = self.message.as_ref() {
-            string = format!("This is the controller Telem page\n\nThe telem message is: \n {}", message);
-        } else {
-            string = format!("This is the controller Telem page")
-        }
+        let block = Block::bordered()
+            .title("Controller Telem")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        block.render(area, buf);
 
-        Paragraph::new(string)
-            .block(
-                Block::bordered()
-                    .title("Controller Telem")
-                    .title_alignment(Alignment::Center)
-                    .border_type(BorderType::Rounded),
-            )
+        let [header, pad] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(inner);
+        self.render_header(header, buf);
+        render_pad(&self.pad, pad, buf);
+    }
+
+    fn render_header(&self, area: Rect, buf: &mut Buffer) {
+        let now = SystemTime::now();
+        let status = self.status.as_ref().map_or("No controller selected".to_string(), |status| status.to_string());
+        let activity = match self.pad.since_last_event(now) {
+            Some(age) => {
+                let age = age.as_secs_f32();
+                let color = if age < SILENCE_LIMITS_S.0 {
+                    Color::Green
+                } else if age < SILENCE_LIMITS_S.1 {
+                    Color::Yellow
+                } else {
+                    Color::Red
+                };
+                Line::from(vec![
+                    Span::raw(format!("Events: {:.0}/s  Last: ", self.pad.event_rate(now))),
+                    Span::styled(format!("{:.1} s ago", age), Style::default().fg(color)),
+                ])
+            },
+            None => Line::from("Events: none yet"),
+        };
+        let last = self.message.as_deref().unwrap_or("");
+        Paragraph::new(vec![
+            Line::from(format!("{}  |  Drive mode: {}", status, self.drive_mode)),
+            activity,
+            Line::from(last.to_string()),
+        ])
             .centered()
             .render(area, buf);
     }
 
-    pub fn add_telem(&mut self, event: GamepadEventType){
+    pub fn set_drive_mode(&mut self, drive_mode: String) {
+        self.drive_mode = drive_mode;
+    }
+
+    pub fn set_status(&mut self, status: ControllerStatus) {
+        self.status = Some(status);
+    }
+
+    pub fn pad(&self) -> &PadState {
+        &self.pad
+    }
+
+    pub fn add_telem(&mut self, event: GamepadEventType, time: SystemTime){
+        self.pad.apply(&event, time);
+        let at = TimeOfDay(time);
         let mut message: Option<String> = self.message.clone();
         match event {
             GamepadEventType::AxisChanged(axis, val, _) => {
-                message = Some(format!("Axis {:?} changed by {}", axis, val));
+                message = Some(format!("Axis {:?} changed by {} at {}", axis, val, at));
             },
             GamepadEventType::ButtonPressed(button, code) => {
-                message = Some(format!("Button {:?} pressed with code {:?}", button, code));
+                message = Some(format!("Button {:?} pressed with code {:?} at {}", button, code, at));
             },
             GamepadEventType::ButtonReleased(..) => (),
             GamepadEventType::ButtonChanged(..) => (),
             GamepadEventType::ButtonRepeated(..) => (),
             GamepadEventType::Connected => {
-                message = Some(format!("Controller connected",));
+                message = Some(format!("Controller connected at {}", at));
             },
             GamepadEventType::Disconnected => {
-                message = Some(format!("Controller disconnected",));
+                message = Some(format!("Controller disconnected at {}", at));
             },
             _ => ()
         }
         self.message = message;
     }
 
-}
\ No newline at end of file
+}
+
+/// The pad laid out as it is held: triggers and bumpers across the top, sticks at the
+/// sides, d-pad, menu buttons and face buttons between them.
+fn render_pad(pad: &PadState, area: Rect, buf: &mut Buffer) {
+    let [shoulders, body] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+    let [left_trigger, left_bumper, _, right_bumper, right_trigger] = Layout::horizontal([
+        Constraint::Percentage(25),
+        Constraint::Length(6),
+        Constraint::Min(0),
+        Constraint::Length(6),
+        Constraint::Percentage(25),
+    ]).areas(shoulders);
+    render_trigger(pad, "LT", Button::LeftTrigger2, Axis::LeftZ, left_trigger, buf);
+    render_buttons(pad, &[vec![(Button::LeftTrigger, "LB")]], centred(left_bumper, 1), buf);
+    render_buttons(pad, &[vec![(Button::RightTrigger, "RB")]], centred(right_bumper, 1), buf);
+    render_trigger(pad, "RT", Button::RightTrigger2, Axis::RightZ, right_trigger, buf);
+
+    let [left_stick, dpad, menu, face, right_stick] = Layout::horizontal([
+        Constraint::Percentage(25),
+        Constraint::Percentage(16),
+        Constraint::Percentage(18),
+        Constraint::Percentage(16),
+        Constraint::Percentage(25),
+    ]).areas(body);
+    render_stick(pad, "L", (Axis::LeftStickX, Axis::LeftStickY), Button::LeftThumb, left_stick, buf);
+    render_stick(pad, "R", (Axis::RightStickX, Axis::RightStickY), Button::RightThumb, right_stick, buf);
+    // Some pads report the d-pad as an axis pair rather than buttons.
+    let dpad_lit = |button: Button, axis: Axis, sign: f32| pad.is_pressed(button) || pad.axis(axis) * sign > 0.5;
+    let dpad_lines = vec![
+        Line::from(key("^", dpad_lit(Button::DPadUp, Axis::DPadY, 1.0))),
+        Line::from(vec![key("<", dpad_lit(Button::DPadLeft, Axis::DPadX, -1.0)), Span::raw(" "), key(">", dpad_lit(Button::DPadRight, Axis::DPadX, 1.0))]),
+        Line::from(key("v", dpad_lit(Button::DPadDown, Axis::DPadY, -1.0))),
+    ];
+    Paragraph::new(dpad_lines).centered().render(centred(dpad, 3), buf);
+    render_buttons(pad, &[
+        vec![(Button::Mode, "Mode")],
+        vec![(Button::Select, "Sel"), (Button::Start, "Start")],
+    ], centred(menu, 2), buf);
+    render_buttons(pad, &[
+        vec![(Button::North, "Y")],
+        vec![(Button::West, "X"), (Button::East, "B")],
+        vec![(Button::South, "A")],
+    ], centred(face, 3), buf);
+}
+
+/// A band `height` rows tall through the middle of `area`.
+fn centred(area: Rect, height: u16) -> Rect {
+    let [_, middle, _] = Layout::vertical([Constraint::Min(0), Constraint::Length(height), Constraint::Min(0)]).areas(area);
+    middle
+}
+
+fn key(label: &str, lit: bool) -> Span<'static> {
+    let style = if lit { Style::default().fg(Color::Black).bg(Color::Green) } else { Style::default().fg(Color::DarkGray) };
+    Span::styled(format!("[{}]", label), style)
+}
+
+/// Rows of buttons, each lit while pressed.
+fn render_buttons(pad: &PadState, rows: &[Vec<(Button, &str)>], area: Rect, buf: &mut Buffer) {
+    let lines: Vec<Line> = rows.iter()
+        .map(|row| {
+            let mut spans = Vec::new();
+            for (index, (button, label)) in row.iter().enumerate() {
+                if index > 0 {
+                    spans.push(Span::raw(" "));
+                }
+                spans.push(key(label, pad.is_pressed(*button)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    Paragraph::new(lines).centered().render(area, buf);
+}
+
+/// Analog trigger travel, from the button value or, on pads that report it that way, the Z axis.
+fn render_trigger(pad: &PadState, label: &str, button: Button, axis: Axis, area: Rect, buf: &mut Buffer) {
+    let value = pad.button(button).max(pad.axis(axis)).clamp(0.0, 1.0);
+    Gauge::default()
+        .block(Block::bordered().title(label.to_string()).border_type(BorderType::Rounded))
+        .gauge_style(Style::default().fg(Color::Cyan))
+        .ratio(value as f64)
+        .label(format!("{:.2}", value))
+        .render(area, buf);
+}
+
+/// The stick's position inside its travel circle, which turns green while the stick is clicked.
+fn render_stick(pad: &PadState, label: &str, (x_axis, y_axis): (Axis, Axis), thumb: Button, area: Rect, buf: &mut Buffer) {
+    let (x, y) = (pad.axis(x_axis) as f64, pad.axis(y_axis) as f64);
+    let ring = if pad.is_pressed(thumb) { Color::Green } else { Color::DarkGray };
+    Canvas::default()
+        .block(Block::bordered()
+            .title(format!("{} {:+.2} {:+.2}", label, x, y))
+            .border_type(BorderType::Rounded))
+        .marker(Marker::Braille)
+        .x_bounds([-1.1, 1.1])
+        .y_bounds([-1.1, 1.1])
+        .paint(|ctx| {
+            ctx.draw(&Circle { x: 0.0, y: 0.0, radius: 1.0, color: ring });
+            ctx.draw(&Points { coords: &[(0.0, 0.0)], color: Color::DarkGray });
+            ctx.layer();
+            ctx.draw(&Circle { x, y, radius: 0.12, color: Color::Yellow });
+            ctx.draw(&Points { coords: &[(x, y)], color: Color::Yellow });
+        })
+        .render(area, buf);
+}
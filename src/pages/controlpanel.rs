@@ -1,11 +1,11 @@
 
 use ratatui::{
-    buffer::Buffer, layout::{Alignment, Constraint, Layout, Rect}, style::{ Style, Stylize}, text::{Line, Text}, widgets::{Block, BorderType, List, ListDirection, ListState, StatefulWidget, Widget}
+    buffer::Buffer, layout::{Alignment, Constraint, Layout, Rect}, style::{ Style, Stylize}, widgets::{Block, BorderType, List, ListDirection, ListState, Paragraph, StatefulWidget, Widget}
 };
 
-use crate::{page_functions::*, tasks::{DriverEvent, DriverTask}};
+use crate::{page_functions::*, tasks::DriverEvent, teleop::{Action, BindingAction, DriveMode}};
 
-use super::Page;
+use super::{ConsoleAction, LogAction, Page, ParamAction, PromptPurpose};
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub enum ConfigOption{
@@ -15,15 +15,25 @@ pub enum ConfigOption{
     Window(Window),
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone)]
 pub enum ConfigFnOptions{
     None(fn() -> Option<ControlResult>),
     NoneToWindow(fn() -> (Window, Option<ControlResult>)),
     WindowToWindow(fn(window: &Window) -> (Window, Option<ControlResult>)),
+    WindowToNone(fn(window: &Window) -> Option<ControlResult>),
     ConfigToNone(fn(config: &Config) -> Option<ControlResult>),
     ConfigToWindow(fn(config: &Config) -> (Window, Option<ControlResult>))
 }
 
+/// Callbacks compare by kind only: function pointers have no reliable identity.
+impl PartialEq for ConfigFnOptions {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl Eq for ConfigFnOptions {}
+
 fn hello_world() -> Option<ControlResult>{
     println!("Hello World!");
     None
@@ -40,13 +50,15 @@ impl Default for ConfigFnOptions{
 pub struct Config {
     short_text: String,
     full_text: Option<String>, 
+    /// What the entry stands for when its text is a longer label, e.g. a port path.
+    value: Option<String>,
     option: ConfigOption,
     on_select: Option<ConfigFnOptions>
 }
 
 impl Config{
     pub fn new(name: String) -> Self {
-        Self { short_text: name, full_text: None, option: ConfigOption::default(), on_select: None }
+        Self { short_text: name, full_text: None, value: None, option: ConfigOption::default(), on_select: None }
     }
 
     pub fn with_configoption(mut self, option: ConfigOption) -> Self {
@@ -59,6 +71,11 @@ impl Config{
         self
     }
 
+    pub fn with_value(mut self, value: String) -> Self {
+        self.value = Some(value);
+        self
+    }
+
     pub fn with_on_select(mut self, on_select:ConfigFnOptions) -> Self {
         self.on_select = Some(on_select);
         self
@@ -75,6 +92,10 @@ impl Config{
         }
     }
 
+    pub fn get_value(&self) -> &str {
+        self.value.as_deref().unwrap_or(self.get_short_text())
+    }
+
     
 }
 
@@ -107,6 +128,36 @@ impl Window {
         self
     }
 
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Swaps in new entries, keeping the highlight and any sub-window open under an
+    /// entry whose text is unchanged.
+    pub fn set_configs(&mut self, mut configs: Vec<Config>) {
+        for config in configs.iter_mut() {
+            if let Some(old) = self.content.iter_mut().find(|old| old.short_text == config.short_text) {
+                if matches!(old.option, ConfigOption::Window(_)) {
+                    config.option = std::mem::take(&mut old.option);
+                }
+            }
+        }
+        self.content = configs;
+        if let Some(selected) = self.list_state.selected() {
+            if self.content.is_empty() {
+                self.list_state.select(None);
+            } else if selected >= self.content.len() {
+                self.list_state.select(Some(self.content.len() - 1));
+            }
+        }
+        // Focus was in a sub-window that no longer exists, so take it back here.
+        let has_sub_window = self.list_state.selected()
+            .is_some_and(|selected| matches!(self.content[selected].option, ConfigOption::Window(_)));
+        if !self.window_selected && !has_sub_window {
+            self.window_selected = true;
+        }
+    }
+
     pub fn as_selected(mut self) -> Self {
         self.window_selected = true;
         self
@@ -125,7 +176,11 @@ impl Window {
         let inner_area  = frame.inner(area);
         frame.render(area, buf);
         let items: Vec<String> = self.content.iter().map(|config| 
-            config.get_short_text().to_string()).collect();
+            match config.option {
+                ConfigOption::CheckBox(true) => format!("[x] {}", config.get_short_text()),
+                ConfigOption::CheckBox(false) => format!("[ ] {}", config.get_short_text()),
+                _ => config.get_short_text().to_string(),
+            }).collect();
         let list = List::new(items)
             // .block(Block::bordered()
             // .title(self.name.as_ref())
@@ -229,6 +284,7 @@ impl Window {
             if let Some(selected_content) = self.list_state.selected(){
                 if let Some(config_fn_option) = self.content[selected_content as usize].on_select.clone(){
                     match config_fn_option {
+                        ConfigFnOptions::None(function) => function(),
                         ConfigFnOptions::NoneToWindow(function) => {
                             let config = &mut self.content[selected_content as usize];
                             let (window, result) = function();
@@ -247,13 +303,14 @@ impl Window {
                             config.option = ConfigOption::Window(window);
                             result
                         }
+                        ConfigFnOptions::WindowToNone(function) => function(self),
                         ConfigFnOptions::ConfigToNone(function) =>{
+                            self.check_only(selected_content);
                             match self.content.get(selected_content as usize) {
                                 Some(config) => function(config),
                                 None => None,
                             }
                         }
-                        _ => None,
                     }
                 } else {
                     None
@@ -304,17 +361,53 @@ impl Window {
         self.window_selected = false;
     }
 
+    /// Radio-button behaviour for check box entries: ticks `index` and clears the rest.
+    fn check_only(&mut self, index: usize) {
+        if !matches!(self.content[index].option, ConfigOption::CheckBox(_)) {
+            return;
+        }
+        for (idx, config) in self.content.iter_mut().enumerate() {
+            if let ConfigOption::CheckBox(checked) = &mut config.option {
+                *checked = idx == index;
+            }
+        }
+    }
+
 }
 
 pub enum ControlResult {
     SetController(String),
+    /// Ask the input service which controllers are plugged in.
+    ListControllers,
     ChangePage(Page),
-    DriverChange(DriverEvent)
+    DriverChange(DriverEvent),
+    /// Driver registry controls, keyed by port.
+    StartDriver(String),
+    StopDriver(String),
+    SelectDriver(String),
+    OpenPrompt(PromptPurpose),
+    /// Path of a firmware image to flash through the active driver.
+    UploadFirmware(String),
+    /// Probe every free serial port for a robot.
+    ScanForRobots,
+    Param(ParamAction),
+    Console(ConsoleAction),
+    Log(LogAction),
+    Binding(BindingAction),
+    ToggleInvert(Action),
+    DriveMode(DriveMode),
+    /// Flip field-oriented mecanum drive on or off.
+    ToggleFieldOriented,
+    /// Switch the keyboard between menu navigation and driving.
+    ToggleKeyboardDrive,
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct ControlPanel {
     description: String,
+    status: Option<String>,
+    /// What the keyboard is doing, shown above the status.
+    key_mode: Option<String>,
     main_window: Window,
     selected_window: u16,
 }
@@ -336,6 +429,34 @@ impl ControlPanel {
             config.on_select = Some(ConfigFnOptions::NoneToWindow(select_controller_window));
             config
         });
+        configs.push({
+            let mut config = Config::new("Bindings".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("View and rebind which pad inputs drive which robot actions".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(bindings_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Axis Tuning".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Deadzones, response curve, scaling and smoothing for each stick action".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(tuning_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Drive Mode".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Pick how stick input is mixed into wheel commands for this chassis".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(drive_mode_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Keyboard Drive".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Drive from the keyboard when no pad is available (Tab toggles)".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(keyboard_window));
+            config
+        });
         configs.push({
             let mut config = Config::new("Select Page".to_string())
                 .with_configoption(ConfigOption::default())
@@ -343,11 +464,67 @@ impl ControlPanel {
             config.on_select = Some(ConfigFnOptions::NoneToWindow(list_pages));
             config
         });
+        configs.push({
+            let mut config = Config::new("Drivers".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Start, stop and pick the active driver for each port".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(drivers_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Parameters".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Read, tune and save the active robot's parameters".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(parameters_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Firmware".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Flash a binary or Intel HEX image onto the active driver's robot".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(firmware_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Scan for Robots".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Ask every free serial port who is there".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(scan_window));
+            config
+        });
         configs.push({
             let mut config = Config::new("Driver Port Control".to_string())
                 .with_configoption(ConfigOption::default())
-                .with_fulltext("List and select Driver Port".to_string());
-            config.on_select = Some(ConfigFnOptions::NoneToWindow(DriverTask::list_ports));
+                .with_fulltext("Select the driver port and its line settings".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(port_control_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Serial Console".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Watch and send raw bytes on the active driver's port".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(serial_console_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Robot Log".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Filter and search log output from the robots".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(robot_log_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Driver State".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("Enable, disable, e-stop or reset the robot".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(driver_state_window));
+            config
+        });
+        configs.push({
+            let mut config = Config::new("Drive Rate".to_string())
+                .with_configoption(ConfigOption::default())
+                .with_fulltext("How often drive commands are streamed to the robot".to_string());
+            config.on_select = Some(ConfigFnOptions::NoneToWindow(drive_rate_window));
             config
         });
 
@@ -356,6 +533,8 @@ impl ControlPanel {
 
         Self {
             description: "Terminal Config!".to_string(),
+            status: None,
+            key_mode: None,
             main_window: window,
             selected_window: 0,
         }
@@ -363,20 +542,49 @@ impl ControlPanel {
     }
 
 
+    /// Fills the main-menu entry `title` with entries built from application state,
+    /// which a plain `fn` window builder cannot see.
+    pub fn update_window(&mut self, title: &str, name: String, configs: Vec<Config>) {
+        let Some(config) = self.main_window.content.iter_mut().find(|config| config.short_text == title) else {
+            return;
+        };
+        match &mut config.option {
+            ConfigOption::Window(window) => window.set_configs(configs),
+            option => *option = ConfigOption::Window(Window::new(name).with_configs(configs)),
+        }
+        config.on_select = None;
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    pub fn set_key_mode(&mut self, key_mode: Option<String>) {
+        self.key_mode = key_mode;
+    }
+
     pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        let mut info_text: Option<String> = None;
+        let info_text = self.status.as_ref().unwrap_or(&self.description);
+        let mut info_block = Block::bordered().border_type(BorderType::Rounded);
+        if let Some(key_mode) = &self.key_mode {
+            info_block = info_block.title(key_mode.as_str()).title_style(Style::default().black().on_yellow());
+        }
         let split = Layout::vertical([
             Constraint::Percentage(20),
             Constraint::Percentage(80),
         ]);
         let [info_pane, window] = split.areas(area);
+        Paragraph::new(info_text.as_str())
+            .block(info_block)
+            .render(info_pane, buf);
         let split = Layout::horizontal([
-            Constraint::Percentage(30),
-            Constraint::Percentage(30),
-            Constraint::Percentage(40),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
         ]);
-        let [main_menu, pane1, pane2] =  split.areas(window);
-        let panes = vec![pane2, pane1];
+        let [main_menu, pane1, pane2, pane3] =  split.areas(window);
+        let panes = vec![pane3, pane2, pane1];
         self.main_window.render(main_menu, buf, panes);
     }
     
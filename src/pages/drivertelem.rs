@@ -1,31 +1,105 @@
+use std::time::Duration;
+
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
     widgets::{Block, BorderType, Paragraph, Widget,},
 };
 
-use crate::tasks::DriverEvent;
+use crate::{protocol::RobotIdentity, tasks::{ClockEstimate, CommandStatus, DriverEvent, LinkStats, TelemetrySample, TimeOfDay, Transition, UploadProgress, UploadStage}};
+
+/// Number of recent state-change commands kept for display.
+const COMMAND_HISTORY: usize = 5;
+/// Number of recent state transitions kept for display.
+const TRANSITION_HISTORY: usize = 8;
+
+/// Green/yellow limits for link health figures; anything above the second is red.
+const RTT_LIMITS_MS: (f64, f64) = (50.0, 150.0);
+const LOSS_LIMITS: (f64, f64) = (0.01, 0.05);
+const SILENCE_LIMITS_MS: (f64, f64) = (250.0, 1000.0);
+/// Battery voltages below which it shows yellow, then red.
+const BATTERY_LIMITS_V: (f32, f32) = (11.5, 10.8);
+/// Width of the firmware upload progress bar in characters.
+const PROGRESS_WIDTH: usize = 30;
 
-#[derive(Debug, Clone,  Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct DriverTelem {
     row_index: usize,
+    /// Per-driver telemetry in the order drivers first reported.
+    drivers: Vec<(String, DriverView)>,
+    active: Option<String>,
+}
+
+/// Everything known about one driver's link and robot.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct DriverView {
     message: Option<String>,
+    link: Option<String>,
+    last_error: Option<String>,
+    commands: Vec<CommandStatus>,
+    stats: Option<LinkStats>,
+    transitions: Vec<Transition>,
+    identity: Option<RobotIdentity>,
+    upload: Option<UploadProgress>,
+    clock: Option<ClockEstimate>,
+}
+
+fn health_color(value: f64, (good, warn): (f64, f64)) -> Color {
+    if value <= good {
+        Color::Green
+    } else if value <= warn {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+fn duration_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
 }
 
 impl DriverTelem {
     pub fn new() -> Self {
-        Self { row_index: 0 , message: None}
+        Self::default()
     }
 
-    pub fn render(&self, area: Rect, buf: &mut Buffer) {
-        let string: String;
-        if let Some(message) = self.message.as_ref() {
-            string = format!("This is the Driver Telem page\n\nThe telem message is: \n {}", message);
-        } else {
-            string = format!("This is the driver Telem page")
+    /// Chooses which driver's details are shown; the others only get a summary.
+    pub fn set_active(&mut self, name: Option<&str>) {
+        self.active = name.map(str::to_string);
+    }
+
+    /// Renders the active driver's details along with its latest telemetry, which the
+    /// app keeps so other pages can read it too.
+    pub fn render(&self, area: Rect, buf: &mut Buffer, telemetry: Option<&TelemetrySample>) {
+        let mut lines: Vec<Line> = vec![Line::from("This is the Driver Telem page"), Line::default()];
+        let active = self.drivers.iter()
+            .find(|(name, _)| self.active.as_ref() == Some(name))
+            .or(self.drivers.first());
+        if self.drivers.len() > 1 {
+            let mut spans = vec![Span::raw("Drivers: ")];
+            for (name, view) in self.drivers.iter() {
+                let state = view.message.as_deref().unwrap_or("unknown");
+                let style = if active.is_some_and(|(active, _)| active == name) {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default()
+                };
+                spans.push(Span::styled(format!("{} [{}]  ", name, state), style));
+            }
+            lines.push(Line::from(spans));
+        }
+        if let Some((name, view)) = active {
+            lines.push(Line::from(format!("Driver: {}", name)));
+            view.render_lines(&mut lines);
+        }
+        if let Some(sample) = telemetry {
+            lines.push(Line::default());
+            lines.extend(telemetry_lines(sample));
         }
 
-        Paragraph::new(string)
+        Paragraph::new(lines)
             .block(
                 Block::bordered()
                     .title("Driver Telem")
@@ -36,12 +110,152 @@ impl DriverTelem {
             .render(area, buf);
     }
 
-    pub fn add_telem(&mut self, event: DriverEvent){
-        let mut message: Option<String> = self.message.clone();
+    pub fn add_telem(&mut self, name: String, event: DriverEvent) {
+        let index = match self.drivers.iter().position(|(driver, _)| *driver == name) {
+            Some(index) => index,
+            None => {
+                self.drivers.push((name, DriverView::default()));
+                self.drivers.len() - 1
+            }
+        };
+        self.drivers[index].1.add_telem(event);
+    }
+}
+
+impl DriverView {
+    fn render_lines(&self, lines: &mut Vec<Line<'static>>) {
+        if let Some(identity) = self.identity.as_ref() {
+            lines.push(Line::from(format!("Robot: {} (firmware {})", identity.name, identity.firmware)));
+        }
+        if let Some(message) = self.message.as_ref() {
+            lines.push(Line::from(format!("State: {}", message)));
+        }
+        if let Some(link) = self.link.as_ref() {
+            lines.push(Line::from(format!("Link: {}", link)));
+        }
+        if let Some(stats) = self.stats.as_ref() {
+            lines.extend(Self::stats_lines(stats));
+        }
+        if let Some(clock) = self.clock.as_ref() {
+            lines.push(Line::from(format!("Clock: {}", clock)));
+        }
+        if let Some(upload) = self.upload.as_ref() {
+            lines.push(Self::upload_line(upload));
+        }
+        if let Some(error) = self.last_error.as_ref() {
+            lines.push(Line::default());
+            lines.push(Line::styled(format!("Last error: {}", error), Style::default().fg(Color::Red)));
+        }
+        if !self.commands.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("Commands:"));
+            lines.extend(self.commands.iter().map(|command| Line::from(command.to_string())));
+        }
+        if !self.transitions.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("Transitions:"));
+            lines.extend(self.transitions.iter().map(|transition| Line::from(transition.to_string())));
+        }
+    }
+
+    fn upload_line(upload: &UploadProgress) -> Line<'static> {
+        let color = match upload.stage {
+            UploadStage::Done => Color::Green,
+            UploadStage::Failed(_) => Color::Red,
+            _ => Color::Yellow,
+        };
+        let filled = (upload.fraction() * PROGRESS_WIDTH as f64).round() as usize;
+        let bar = format!("[{}{}] {:>3.0}%", "#".repeat(filled), ".".repeat(PROGRESS_WIDTH - filled), upload.fraction() * 100.0);
+        Line::styled(format!("Firmware {} {}", bar, upload), Style::default().fg(color))
+    }
+
+    fn stats_lines(stats: &LinkStats) -> Vec<Line<'static>> {
+        let rtt = match stats.rtt {
+            Some(rtt) => Span::styled(format!("{:.1} ms", duration_ms(rtt)),
+                Style::default().fg(health_color(duration_ms(rtt), RTT_LIMITS_MS))),
+            None => Span::styled("no reply", Style::default().fg(Color::Red)),
+        };
+        let silence = match stats.since_last_frame {
+            Some(silence) => Span::styled(format!("{:.0} ms ago", duration_ms(silence)),
+                Style::default().fg(health_color(duration_ms(silence), SILENCE_LIMITS_MS))),
+            None => Span::styled("never", Style::default().fg(Color::Red)),
+        };
+        let loss = stats.loss_ratio();
+        let corrupt_color = if stats.frames_corrupt == 0 { Color::Green } else { Color::Yellow };
+        vec![
+            Line::from(vec![Span::raw("RTT: "), rtt, Span::raw("  Last frame: "), silence]),
+            Line::from(vec![
+                Span::raw(format!("Frames: {} received, ", stats.frames_received)),
+                Span::styled(format!("{} lost ({:.1}%)", stats.frames_lost, loss * 100.0),
+                    Style::default().fg(health_color(loss, LOSS_LIMITS))),
+                Span::raw(format!(", {} out of order, ", stats.frames_out_of_order)),
+                Span::styled(format!("{} corrupt", stats.frames_corrupt), Style::default().fg(corrupt_color)),
+            ]),
+            Line::from(format!("Throughput: rx {:.0} B/s, tx {:.0} B/s",
+                stats.rx_bytes_per_sec, stats.tx_bytes_per_sec)),
+        ]
+    }
+
+    fn add_telem(&mut self, event: DriverEvent){
         match event {
-            DriverEvent::StateReport(state) => message = Some(state.to_string()),
+            DriverEvent::StateReport(state) => self.message = Some(state.to_string()),
+            DriverEvent::Command(status) => self.update_command(status),
+            DriverEvent::Transition(transition) => {
+                self.transitions.push(transition);
+                if self.transitions.len() > TRANSITION_HISTORY {
+                    self.transitions.remove(0);
+                }
+            },
+            DriverEvent::Link(status) => self.link = Some(status.to_string()),
+            DriverEvent::LinkStats(stats) => self.stats = Some(stats),
+            DriverEvent::Identity(identity) => self.identity = Some(identity),
+            DriverEvent::UploadProgress(progress) => self.upload = Some(progress),
+            DriverEvent::Clock(estimate) => self.clock = Some(estimate),
+            DriverEvent::StateChangeError(e) => self.last_error = Some(e),
+            DriverEvent::ProtocolError(e) => self.last_error = Some(e.to_string()),
+            DriverEvent::Error(e) => self.last_error = Some(e.to_string()),
             _ => (),
         }
-        self.message = message;
     }
+
+    fn update_command(&mut self, status: CommandStatus) {
+        match self.commands.iter_mut().find(|command| command.seq == status.seq) {
+            Some(command) => *command = status,
+            None => {
+                self.commands.push(status);
+                if self.commands.len() > COMMAND_HISTORY {
+                    self.commands.remove(0);
+                }
+            }
+        }
+    }
+}
+
+fn telemetry_lines(sample: &TelemetrySample) -> Vec<Line<'static>> {
+    let telemetry = &sample.telemetry;
+    let at = match sample.ground_time {
+        Some(time) => TimeOfDay(time).to_string(),
+        None => "clock not synced".to_string(),
+    };
+    let battery_color = match telemetry.battery_v {
+        volts if volts >= BATTERY_LIMITS_V.0 => Color::Green,
+        volts if volts >= BATTERY_LIMITS_V.1 => Color::Yellow,
+        _ => Color::Red,
+    };
+    let fault_color = if telemetry.faults.is_empty() { Color::Green } else { Color::Red };
+    let mut lines = vec![
+        Line::from(format!("Telemetry at robot {:.3} s ({})", sample.robot_us as f64 / 1e6, at)),
+        Line::from(vec![
+            Span::raw("Battery: "),
+            Span::styled(format!("{:.2} V", telemetry.battery_v), Style::default().fg(battery_color)),
+            Span::raw(format!("  MCU: {:.1} °C  Faults: ", telemetry.mcu_temperature_c)),
+            Span::styled(telemetry.faults.to_string(), Style::default().fg(fault_color)),
+        ]),
+    ];
+    lines.extend(telemetry.motors.iter().enumerate().map(|(index, motor)| Line::from(format!(
+        "Motor {}: {:>6.2} A  {:>9} ticks  {:>5.1} °C", index, motor.current_a, motor.encoder_ticks, motor.temperature_c))));
+    if let Some(heading) = telemetry.heading_deg {
+        lines.push(Line::from(format!("Heading: {:+.1}°", heading)));
+    }
+    lines
 }
@@ -0,0 +1,132 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, BorderType, Paragraph, Widget},
+};
+
+use crate::{protocol::ParamInfo, tasks::ParamEvent};
+
+/// Parameter edits picked in the control panel, by parameter name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamAction {
+    Refresh,
+    Save,
+    Read(String),
+    Toggle(String),
+    ResetDefault(String),
+    /// Value typed into the prompt, parsed against the parameter's type.
+    Set(String, String),
+}
+
+/// The active driver's parameter table, as last reported by the robot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParametersPage {
+    source: Option<String>,
+    params: Vec<ParamInfo>,
+    count: Option<u16>,
+    message: Option<(String, bool)>,
+}
+
+impl ParametersPage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follows a different driver, forgetting the old table.
+    pub fn set_source(&mut self, source: Option<&str>) {
+        if self.source.as_deref() != source {
+            *self = Self { source: source.map(str::to_string), ..Self::default() };
+        }
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ParamInfo> {
+        self.params.iter().find(|info| info.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.params.iter().map(|info| info.name.clone()).collect()
+    }
+
+    pub fn set_message(&mut self, message: String, is_error: bool) {
+        self.message = Some((message, is_error));
+    }
+
+    /// Applies a reply from the robot; returns true when the set of parameters changed.
+    pub fn apply(&mut self, event: ParamEvent) -> bool {
+        match event {
+            ParamEvent::Info { count, info } => {
+                self.count = Some(count);
+                match self.params.iter_mut().find(|known| known.index == info.index) {
+                    Some(known) => {
+                        let renamed = known.name != info.name;
+                        *known = info;
+                        renamed
+                    }
+                    None => {
+                        let position = self.params.partition_point(|known| known.index < info.index);
+                        self.params.insert(position, info);
+                        true
+                    }
+                }
+            }
+            ParamEvent::Value { index, value } => {
+                if let Some(info) = self.params.iter_mut().find(|info| info.index == index) {
+                    info.value = value;
+                    self.message = Some((format!("{} = {}", info.name, value), false));
+                }
+                false
+            }
+            ParamEvent::Saved => {
+                self.message = Some(("Parameters saved to robot flash".to_string(), false));
+                false
+            }
+            ParamEvent::Failed(reason) => {
+                self.message = Some((reason, true));
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines = vec![Line::from(format!("{:<24} {:<6} {:>12} {:>24} {:>12}", "Name", "Type", "Value", "Range", "Default"))];
+        for info in self.params.iter() {
+            // Anything changed from the robot's default is starred and highlighted.
+            let (marker, style) = if info.is_default() {
+                (' ', Style::default())
+            } else {
+                ('*', Style::default().fg(Color::Yellow))
+            };
+            lines.push(Line::styled(format!("{:<24} {:<6} {:>11}{} {:>24} {:>12}",
+                info.name, info.value.kind(), info.value.to_string(), marker,
+                format!("{} .. {}", info.min, info.max), info.default.to_string()), style));
+        }
+        if self.params.is_empty() {
+            lines.push(Line::from("No parameters yet; use Parameters > Refresh"));
+        }
+        if let Some((message, is_error)) = self.message.as_ref() {
+            let color = if *is_error { Color::Red } else { Color::Green };
+            lines.push(Line::default());
+            lines.push(Line::styled(message.clone(), Style::default().fg(color)));
+        }
+
+        let title = match (self.source.as_ref(), self.count) {
+            (Some(source), Some(count)) => format!("Parameters: {} ({}/{})", source, self.params.len(), count),
+            (Some(source), None) => format!("Parameters: {}", source),
+            (None, _) => "Parameters: no active driver".to_string(),
+        };
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,86 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, BorderType, Clear, Paragraph, Widget},
+};
+
+use crate::{tasks::NetProtocol, teleop::{Action, Stage}};
+
+use super::SendFormat;
+
+/// What the line typed into a [`Prompt`] is used for once submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptPurpose {
+    ConsoleSend(SendFormat),
+    FirmwarePath,
+    /// New value for the named robot parameter.
+    ParamValue(String),
+    /// `host:port` of a robot to drive over the network.
+    NetworkAddress(NetProtocol),
+    /// New setting for one conditioning stage of an axis action.
+    Conditioning(Action, Stage),
+    /// Text to filter the robot log by.
+    LogSearch,
+    /// Keyboard drive ramp rate, per second.
+    KeyboardRamp,
+}
+
+impl PromptPurpose {
+    fn title(&self) -> String {
+        let title = match self {
+            PromptPurpose::ConsoleSend(SendFormat::Text) => "Send text",
+            PromptPurpose::ConsoleSend(SendFormat::Hex) => "Send hex bytes",
+            PromptPurpose::ConsoleSend(SendFormat::Escaped) => "Send escaped text",
+            PromptPurpose::FirmwarePath => "Firmware image path",
+            PromptPurpose::LogSearch => "Search robot log",
+            PromptPurpose::KeyboardRamp => "Keyboard ramp rate (per second)",
+            PromptPurpose::ParamValue(name) => return format!("New value for {}", name),
+            PromptPurpose::Conditioning(action, stage) => return format!("New {} for {}", stage, action),
+            PromptPurpose::NetworkAddress(protocol) => return format!("{} robot address (host:port)", protocol),
+        };
+        title.to_string()
+    }
+}
+
+/// Single-line text input that takes over the keyboard until submitted or cancelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+    purpose: PromptPurpose,
+    text: String,
+}
+
+impl Prompt {
+    pub fn new(purpose: PromptPurpose) -> Self {
+        Self { purpose, text: String::new() }
+    }
+
+    pub fn purpose(&self) -> &PromptPurpose {
+        &self.purpose
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    pub fn backspace(&mut self) {
+        self.text.pop();
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        Paragraph::new(format!("{}_", self.text))
+            .block(
+                Block::bordered()
+                    .title(format!("{} (Enter to send, Esc to cancel)", self.purpose.title()))
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().light_cyan()),
+            )
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, BorderType, Paragraph, Widget},
+};
+
+use crate::{protocol::LogLevel, tasks::{LogEntry, TimeOfDay}};
+
+/// Log lines kept across all drivers; older ones are dropped.
+const LOG_HISTORY: usize = 1000;
+/// Lines moved per scroll action.
+const SCROLL_STEP: usize = 10;
+
+/// Control-panel actions on the robot log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogAction {
+    /// Hides everything less severe than the level.
+    MinLevel(LogLevel),
+    /// Keeps only lines whose tag or text contains the string, ignoring case.
+    Search(String),
+    ClearSearch,
+    ScrollUp,
+    ScrollDown,
+    /// Back to following the newest lines.
+    ScrollToEnd,
+    Clear,
+}
+
+/// Firmware log output from every driver, newest at the bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobotLog {
+    /// Each line with the driver it came from.
+    entries: VecDeque<(String, LogEntry)>,
+    min_level: LogLevel,
+    search: Option<String>,
+    /// Matching lines hidden below the view; zero follows new output.
+    scroll: usize,
+    /// Lines dropped off the front of the history.
+    dropped: usize,
+}
+
+impl Default for RobotLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            min_level: LogLevel::Info,
+            search: None,
+            scroll: 0,
+            dropped: 0,
+        }
+    }
+}
+
+impl RobotLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_entry(&mut self, driver: String, entry: LogEntry) {
+        // Keep a scrolled-back view on the same lines as new ones arrive below it.
+        if self.scroll > 0 && self.matches(&entry) {
+            self.scroll += 1;
+        }
+        self.entries.push_back((driver, entry));
+        if self.entries.len() > LOG_HISTORY {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+    }
+
+    pub fn apply(&mut self, action: LogAction) {
+        match action {
+            LogAction::MinLevel(level) => {
+                self.min_level = level;
+                self.scroll = 0;
+            },
+            LogAction::Search(text) => {
+                let text = text.trim().to_lowercase();
+                self.search = (!text.is_empty()).then_some(text);
+                self.scroll = 0;
+            },
+            LogAction::ClearSearch => {
+                self.search = None;
+                self.scroll = 0;
+            },
+            LogAction::ScrollUp => self.scroll = (self.scroll + SCROLL_STEP).min(self.visible().count().saturating_sub(1)),
+            LogAction::ScrollDown => self.scroll = self.scroll.saturating_sub(SCROLL_STEP),
+            LogAction::ScrollToEnd => self.scroll = 0,
+            LogAction::Clear => {
+                self.entries.clear();
+                self.scroll = 0;
+                self.dropped = 0;
+            },
+        }
+    }
+
+    fn matches(&self, entry: &LogEntry) -> bool {
+        entry.level <= self.min_level
+            && self.search.as_ref().is_none_or(|search| {
+                entry.tag.to_lowercase().contains(search) || entry.text.to_lowercase().contains(search)
+            })
+    }
+
+    /// Lines that pass the level filter and search, oldest first.
+    pub fn visible(&self) -> impl DoubleEndedIterator<Item = &(String, LogEntry)> {
+        self.entries.iter().filter(|(_, entry)| self.matches(entry))
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let rows = area.height.saturating_sub(2) as usize;
+        let mut lines: Vec<Line> = self.visible()
+            .rev()
+            .skip(self.scroll)
+            .take(rows)
+            .map(|(driver, entry)| Self::entry_line(driver, entry))
+            .collect();
+        lines.reverse();
+
+        let mut title = format!("Robot Log: {} and above", self.min_level);
+        if let Some(search) = &self.search {
+            title.push_str(&format!(", matching \"{}\"", search));
+        }
+        if self.scroll > 0 {
+            title.push_str(&format!(" ({} newer below)", self.scroll));
+        }
+        if self.dropped > 0 {
+            title.push_str(&format!(" ({} dropped)", self.dropped));
+        }
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+
+    fn entry_line(driver: &str, entry: &LogEntry) -> Line<'static> {
+        let color = match entry.level {
+            LogLevel::Error => Color::Red,
+            LogLevel::Warn => Color::Yellow,
+            LogLevel::Info => Color::White,
+            LogLevel::Debug => Color::Cyan,
+            LogLevel::Trace => Color::DarkGray,
+        };
+        Line::styled(
+            format!("{} {} {:<5} [{}] {}", TimeOfDay(entry.at), driver, entry.level, entry.tag, entry.text),
+            Style::default().fg(color),
+        )
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, BorderType, Paragraph, Widget},
+};
+
+use crate::tasks::{RawChunk, RawDirection, TimeOfDay};
+
+/// Chunks kept for scrollback; older ones are dropped.
+const CONSOLE_HISTORY: usize = 256;
+const BYTES_PER_ROW: usize = 16;
+
+/// How a line typed into the console's send prompt is turned into bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendFormat {
+    /// Sent as typed.
+    Text,
+    /// Pairs of hex digits, optionally separated by spaces or `0x` prefixes.
+    Hex,
+    /// Text with `\n`, `\r`, `\t`, `\0`, `\\` and `\xHH` escapes.
+    Escaped,
+}
+
+impl SendFormat {
+    pub fn parse(self, input: &str) -> Result<Vec<u8>, String> {
+        match self {
+            SendFormat::Text => Ok(input.as_bytes().to_vec()),
+            SendFormat::Hex => parse_hex(input),
+            SendFormat::Escaped => parse_escaped(input),
+        }
+    }
+}
+
+fn parse_hex(input: &str) -> Result<Vec<u8>, String> {
+    let digits: String = input
+        .split_whitespace()
+        .map(|word| word.trim_start_matches("0x").trim_start_matches("0X"))
+        .collect();
+    if !digits.is_ascii() {
+        return Err("hex input can only contain hex digits".to_string());
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err("hex input needs an even number of digits".to_string());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| format!("invalid hex byte '{}'", &digits[i..i + 2])))
+        .collect()
+}
+
+fn parse_escaped(input: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16).map_err(|_| format!("invalid escape '\\x{}'", hex))?;
+                bytes.push(byte);
+            }
+            Some(other) => return Err(format!("unknown escape '\\{}'", other)),
+            None => return Err("trailing '\\'".to_string()),
+        }
+    }
+    Ok(bytes)
+}
+
+/// Control-panel actions on the console that don't involve typing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleAction {
+    TogglePause,
+    Clear,
+}
+
+/// Raw hex/ASCII monitor of the active driver's port.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerialConsole {
+    source: Option<String>,
+    chunks: VecDeque<RawChunk>,
+    paused: bool,
+    /// Chunks that arrived while paused and were not kept.
+    skipped: usize,
+    last_error: Option<String>,
+}
+
+impl SerialConsole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follows a different driver, starting from an empty screen.
+    pub fn set_source(&mut self, source: Option<&str>) {
+        if self.source.as_deref() != source {
+            self.source = source.map(str::to_string);
+            self.clear();
+        }
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn apply(&mut self, action: ConsoleAction) {
+        match action {
+            ConsoleAction::TogglePause => self.paused = !self.paused,
+            ConsoleAction::Clear => self.clear(),
+        }
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.last_error = Some(error);
+    }
+
+    fn clear(&mut self) {
+        self.chunks.clear();
+        self.skipped = 0;
+        self.last_error = None;
+    }
+
+    pub fn add_chunk(&mut self, chunk: RawChunk) {
+        if self.paused {
+            self.skipped += 1;
+            return;
+        }
+        self.chunks.push_back(chunk);
+        if self.chunks.len() > CONSOLE_HISTORY {
+            self.chunks.pop_front();
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = Vec::new();
+        for chunk in self.chunks.iter() {
+            lines.extend(Self::chunk_lines(chunk));
+        }
+        let mut status = Vec::new();
+        if self.paused {
+            status.push(Line::styled(format!("PAUSED ({} chunks skipped)", self.skipped), Style::default().fg(Color::Yellow)));
+        }
+        if let Some(error) = self.last_error.as_ref() {
+            status.push(Line::styled(format!("Send failed: {}", error), Style::default().fg(Color::Red)));
+        }
+        // Keep the newest rows in view.
+        let rows = (area.height.saturating_sub(2) as usize).saturating_sub(status.len());
+        let skip = lines.len().saturating_sub(rows);
+        let mut lines: Vec<Line> = lines.into_iter().skip(skip).collect();
+        lines.extend(status);
+
+        let title = match self.source.as_ref() {
+            Some(source) => format!("Serial Console: {}", source),
+            None => "Serial Console: no active driver".to_string(),
+        };
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded),
+            )
+            .render(area, buf);
+    }
+
+    fn chunk_lines(chunk: &RawChunk) -> Vec<Line<'static>> {
+        let (label, color) = match chunk.direction {
+            RawDirection::Rx => ("RX", Color::Green),
+            RawDirection::Tx => ("TX", Color::Cyan),
+        };
+        let style = Style::default().fg(color);
+        let mut lines = vec![Line::styled(format!("{} {} {} bytes", TimeOfDay(chunk.at), label, chunk.bytes.len()), style)];
+        for row in chunk.bytes.chunks(BYTES_PER_ROW) {
+            let hex: Vec<String> = row.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let ascii: String = row.iter()
+                .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+                .collect();
+            lines.push(Line::styled(format!("  {:<width$}  |{}|", hex.join(" "), ascii, width = BYTES_PER_ROW * 3 - 1), style));
+        }
+        lines
+    }
+}
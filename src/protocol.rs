@@ -0,0 +1,14 @@
+pub mod cobs;
+mod crc;
+mod frame;
+mod log;
+mod message;
+mod param;
+mod telemetry;
+
+pub use crc::{crc16, crc32};
+pub use frame::{FrameDecoder, ProtocolError, MAX_FRAME_LEN};
+pub use log::LogLevel;
+pub use message::{DriveCommand, Message, MessageId, Packet, RobotIdentity};
+pub use param::{ParamInfo, ParamValue};
+pub use telemetry::{FaultFlags, MotorTelemetry, RobotTelemetry};
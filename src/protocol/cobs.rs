@@ -0,0 +1,46 @@
+/// Consistent Overhead Byte Stuffing encode. The output never contains a zero,
+/// so a single `0x00` can be used as the frame delimiter on the wire.
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 254 + 2);
+    let mut code_index = 0;
+    let mut code: u8 = 1;
+    out.push(0);
+    for byte in data {
+        if *byte == 0 {
+            out[code_index] = code;
+            code_index = out.len();
+            out.push(0);
+            code = 1;
+        } else {
+            out.push(*byte);
+            code += 1;
+            if code == 0xFF {
+                out[code_index] = code;
+                code_index = out.len();
+                out.push(0);
+                code = 1;
+            }
+        }
+    }
+    out[code_index] = code;
+    out
+}
+
+/// Reverses [`encode`]. Returns `None` when the input is not valid COBS.
+pub fn decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut index = 0;
+    while index < data.len() {
+        let code = data[index] as usize;
+        let end = index + code;
+        if code == 0 || end > data.len() {
+            return None;
+        }
+        out.extend_from_slice(&data[index + 1..end]);
+        index = end;
+        if code != 0xFF && index < data.len() {
+            out.push(0);
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+use super::{cobs, crc16, Message, MessageId, Packet};
+
+/// Frames longer than this are dropped by the decoder.
+pub const MAX_FRAME_LEN: usize = 1024;
+
+/// Frame header is the message id and a little-endian sequence number.
+const HEADER_LEN: usize = 3;
+const CRC_LEN: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    InvalidCobs,
+    TooShort(usize),
+    Oversized(usize),
+    BadCrc { expected: u16, actual: u16 },
+    UnknownMessage(u8),
+    BadPayload(MessageId),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::InvalidCobs => write!(f, "Frame is not valid COBS"),
+            ProtocolError::TooShort(len) => write!(f, "Frame too short ({} bytes)", len),
+            ProtocolError::Oversized(len) => write!(f, "Frame too long ({} bytes)", len),
+            ProtocolError::BadCrc { expected, actual } => {
+                write!(f, "CRC mismatch: expected {:04X}, got {:04X}", expected, actual)
+            }
+            ProtocolError::UnknownMessage(id) => write!(f, "Unknown message id {:#04X}", id),
+            ProtocolError::BadPayload(id) => write!(f, "Malformed {:?} payload", id),
+        }
+    }
+}
+
+impl Packet {
+    /// Encodes the packet into a delimited frame ready to be written to the wire.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(HEADER_LEN + CRC_LEN + 16);
+        raw.push(self.message.id() as u8);
+        raw.extend_from_slice(&self.seq.to_le_bytes());
+        self.message.encode_payload(&mut raw);
+        let crc = crc16(&raw);
+        raw.extend_from_slice(&crc.to_le_bytes());
+        let mut frame = cobs::encode(&raw);
+        frame.push(0);
+        frame
+    }
+
+    fn decode(frame: &[u8]) -> Result<Self, ProtocolError> {
+        let raw = cobs::decode(frame).ok_or(ProtocolError::InvalidCobs)?;
+        if raw.len() < HEADER_LEN + CRC_LEN {
+            return Err(ProtocolError::TooShort(raw.len()));
+        }
+        let (body, crc) = raw.split_at(raw.len() - CRC_LEN);
+        let actual = u16::from_le_bytes([crc[0], crc[1]]);
+        let expected = crc16(body);
+        if actual != expected {
+            return Err(ProtocolError::BadCrc { expected, actual });
+        }
+        let id = MessageId::try_from(body[0])?;
+        let seq = u16::from_le_bytes([body[1], body[2]]);
+        let message = Message::decode(id, &body[HEADER_LEN..])?;
+        Ok(Packet { seq, message })
+    }
+}
+
+/// Splits an incoming byte stream on the `0x00` delimiter and decodes each frame.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    overflowed: bool,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds received bytes into the decoder, returning every frame they complete.
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<Result<Packet, ProtocolError>> {
+        let mut packets = Vec::new();
+        for byte in bytes {
+            if *byte != 0 {
+                if self.buffer.len() < MAX_FRAME_LEN {
+                    self.buffer.push(*byte);
+                } else {
+                    self.overflowed = true;
+                }
+                continue;
+            }
+            if self.overflowed {
+                packets.push(Err(ProtocolError::Oversized(self.buffer.len())));
+            } else if !self.buffer.is_empty() {
+                packets.push(Packet::decode(&self.buffer));
+            }
+            self.buffer.clear();
+            self.overflowed = false;
+        }
+        packets
+    }
+}
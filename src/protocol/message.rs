@@ -0,0 +1,465 @@
+use crate::tasks::DriverState;
+
+use super::{LogLevel, ParamInfo, ParamValue, ProtocolError, RobotTelemetry};
+
+/// Identifies the payload carried by a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MessageId {
+    Heartbeat = 0x01,
+    HeartbeatAck = 0x02,
+    Nack = 0x03,
+    Hello = 0x04,
+    Identity = 0x05,
+    TimeRequest = 0x06,
+    TimeReply = 0x07,
+    StateCommand = 0x10,
+    StateAck = 0x11,
+    Telemetry = 0x20,
+    Log = 0x21,
+    Drive = 0x30,
+    BootEnter = 0x40,
+    BootReady = 0x41,
+    FlashWrite = 0x42,
+    FlashAck = 0x43,
+    FlashVerify = 0x44,
+    FlashVerified = 0x45,
+    Reboot = 0x46,
+    ParamList = 0x50,
+    ParamInfo = 0x51,
+    ParamGet = 0x52,
+    ParamSet = 0x53,
+    ParamValue = 0x54,
+    ParamSave = 0x55,
+    ParamSaved = 0x56,
+}
+
+impl TryFrom<u8> for MessageId {
+    type Error = ProtocolError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(MessageId::Heartbeat),
+            0x02 => Ok(MessageId::HeartbeatAck),
+            0x03 => Ok(MessageId::Nack),
+            0x04 => Ok(MessageId::Hello),
+            0x05 => Ok(MessageId::Identity),
+            0x06 => Ok(MessageId::TimeRequest),
+            0x07 => Ok(MessageId::TimeReply),
+            0x10 => Ok(MessageId::StateCommand),
+            0x11 => Ok(MessageId::StateAck),
+            0x20 => Ok(MessageId::Telemetry),
+            0x21 => Ok(MessageId::Log),
+            0x30 => Ok(MessageId::Drive),
+            0x40 => Ok(MessageId::BootEnter),
+            0x41 => Ok(MessageId::BootReady),
+            0x42 => Ok(MessageId::FlashWrite),
+            0x43 => Ok(MessageId::FlashAck),
+            0x44 => Ok(MessageId::FlashVerify),
+            0x45 => Ok(MessageId::FlashVerified),
+            0x46 => Ok(MessageId::Reboot),
+            0x50 => Ok(MessageId::ParamList),
+            0x51 => Ok(MessageId::ParamInfo),
+            0x52 => Ok(MessageId::ParamGet),
+            0x53 => Ok(MessageId::ParamSet),
+            0x54 => Ok(MessageId::ParamValue),
+            0x55 => Ok(MessageId::ParamSave),
+            0x56 => Ok(MessageId::ParamSaved),
+            other => Err(ProtocolError::UnknownMessage(other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// Liveness probe carrying the sender's timestamp in milliseconds.
+    Heartbeat(u32),
+    /// Reply to a heartbeat, echoing its timestamp.
+    HeartbeatAck(u32),
+    /// Refusal of the command sent with `ack_seq`, with a robot-defined reason code.
+    Nack { ack_seq: u16, code: u8 },
+    /// Handshake probe sent by the ground station when a link comes up.
+    Hello,
+    /// The robot's answer to [`Message::Hello`].
+    Identity(RobotIdentity),
+    StateCommand(DriverState),
+    /// Confirms the command sent with `ack_seq` and reports the resulting state.
+    StateAck { ack_seq: u16, state: DriverState },
+    /// Clock sync probe stamped with ground-station time in microseconds since the epoch.
+    TimeRequest { ground_us: u64 },
+    /// Echoes the probe with the robot's clock, in microseconds, when it arrived and left.
+    TimeReply { ground_us: u64, robot_rx_us: u64, robot_tx_us: u64 },
+    /// A telemetry sample stamped with the robot's clock.
+    Telemetry { robot_us: u64, telemetry: RobotTelemetry },
+    /// A line of firmware log output from subsystem `tag`; the text runs to the end of the frame.
+    Log { robot_us: u64, level: LogLevel, tag: String, text: String },
+    Drive(DriveCommand),
+    /// Asks the robot to drop into its bootloader to flash an image linked at
+    /// `base_address`; a robot whose application starts elsewhere refuses. Raw
+    /// binaries carry no address and send none.
+    BootEnter { base_address: Option<u32> },
+    /// The bootloader is listening and takes writes of at most `max_chunk` bytes.
+    BootReady { max_chunk: u16 },
+    /// Image bytes to store at `offset` from the start of the application region.
+    FlashWrite { offset: u32, data: Vec<u8> },
+    FlashAck { offset: u32 },
+    /// Asks the bootloader to CRC the first `length` bytes it stored.
+    FlashVerify { length: u32, crc: u32 },
+    /// The CRC-32 the bootloader computed over what it stored.
+    FlashVerified { crc: u32 },
+    /// Leaves the bootloader and starts the application.
+    Reboot,
+    /// Asks for one [`Message::ParamInfo`] per parameter.
+    ParamList,
+    /// One entry of the robot's parameter table, out of `count`.
+    ParamInfo { count: u16, info: ParamInfo },
+    ParamGet { index: u16 },
+    ParamSet { index: u16, value: ParamValue },
+    /// Answers a get or set sent with `ack_seq` with the value now in effect.
+    ParamValue { ack_seq: u16, index: u16, value: ParamValue },
+    /// Asks the robot to store its current parameters in flash.
+    ParamSave,
+    ParamSaved { ack_seq: u16 },
+}
+
+/// Who is on the other end of the link.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotIdentity {
+    pub name: String,
+    pub firmware: String,
+}
+
+impl RobotIdentity {
+    fn encode(&self, out: &mut Vec<u8>) {
+        encode_str(&self.name, out);
+        encode_str(&self.firmware, out);
+    }
+
+    fn decode(payload: &[u8]) -> Option<Self> {
+        let (name, rest) = decode_str(payload)?;
+        let (firmware, rest) = decode_str(rest)?;
+        rest.is_empty().then_some(Self { name, firmware })
+    }
+}
+
+/// Strings go on the wire as a length byte followed by UTF-8, truncated to 255 bytes.
+pub(super) fn encode_str(value: &str, out: &mut Vec<u8>) {
+    let mut end = value.len().min(u8::MAX as usize);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    out.push(end as u8);
+    out.extend_from_slice(&value.as_bytes()[..end]);
+}
+
+pub(super) fn decode_str(payload: &[u8]) -> Option<(String, &[u8])> {
+    let (len, rest) = payload.split_first()?;
+    let len = *len as usize;
+    if rest.len() < len {
+        return None;
+    }
+    let value = String::from_utf8(rest[..len].to_vec()).ok()?;
+    Some((value, &rest[len..]))
+}
+
+/// Motor commands streamed to the robot; speeds are in `-1.0..=1.0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DriveCommand {
+    /// Wheel speeds in [`DriveCommand::FRONT_LEFT`] .. [`DriveCommand::REAR_RIGHT`] order;
+    /// a differential chassis gets the same speed front and rear on each side.
+    pub wheels: [f32; 4],
+    pub arm: f32,
+    /// One bit per robot function switched on.
+    pub buttons: u32,
+}
+
+impl DriveCommand {
+    pub const FRONT_LEFT: usize = 0;
+    pub const FRONT_RIGHT: usize = 1;
+    pub const REAR_LEFT: usize = 2;
+    pub const REAR_RIGHT: usize = 3;
+
+    /// Everything stopped and switched off.
+    pub fn neutral() -> Self {
+        Self::default()
+    }
+
+    const ENCODED_LEN: usize = 14;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        for speed in self.wheels.iter().chain([&self.arm]) {
+            let scaled = (speed.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+            out.extend_from_slice(&scaled.to_le_bytes());
+        }
+        out.extend_from_slice(&self.buttons.to_le_bytes());
+    }
+
+    fn decode(payload: &[u8]) -> Option<Self> {
+        if payload.len() != Self::ENCODED_LEN {
+            return None;
+        }
+        let speed = |index: usize| {
+            i16::from_le_bytes([payload[index * 2], payload[index * 2 + 1]]) as f32 / i16::MAX as f32
+        };
+        Some(Self {
+            wheels: [speed(0), speed(1), speed(2), speed(3)],
+            arm: speed(4),
+            buttons: u32::from_le_bytes(payload[10..14].try_into().ok()?),
+        })
+    }
+}
+
+impl Message {
+    pub fn id(&self) -> MessageId {
+        match self {
+            Message::Heartbeat(_) => MessageId::Heartbeat,
+            Message::HeartbeatAck(_) => MessageId::HeartbeatAck,
+            Message::Nack { .. } => MessageId::Nack,
+            Message::Hello => MessageId::Hello,
+            Message::Identity(_) => MessageId::Identity,
+            Message::StateCommand(_) => MessageId::StateCommand,
+            Message::StateAck { .. } => MessageId::StateAck,
+            Message::TimeRequest { .. } => MessageId::TimeRequest,
+            Message::TimeReply { .. } => MessageId::TimeReply,
+            Message::Telemetry { .. } => MessageId::Telemetry,
+            Message::Log { .. } => MessageId::Log,
+            Message::Drive(_) => MessageId::Drive,
+            Message::BootEnter { .. } => MessageId::BootEnter,
+            Message::BootReady { .. } => MessageId::BootReady,
+            Message::FlashWrite { .. } => MessageId::FlashWrite,
+            Message::FlashAck { .. } => MessageId::FlashAck,
+            Message::FlashVerify { .. } => MessageId::FlashVerify,
+            Message::FlashVerified { .. } => MessageId::FlashVerified,
+            Message::Reboot => MessageId::Reboot,
+            Message::ParamList => MessageId::ParamList,
+            Message::ParamInfo { .. } => MessageId::ParamInfo,
+            Message::ParamGet { .. } => MessageId::ParamGet,
+            Message::ParamSet { .. } => MessageId::ParamSet,
+            Message::ParamValue { .. } => MessageId::ParamValue,
+            Message::ParamSave => MessageId::ParamSave,
+            Message::ParamSaved { .. } => MessageId::ParamSaved,
+        }
+    }
+
+    pub(super) fn encode_payload(&self, out: &mut Vec<u8>) {
+        match self {
+            Message::Heartbeat(stamp) | Message::HeartbeatAck(stamp) => {
+                out.extend_from_slice(&stamp.to_le_bytes())
+            }
+            Message::Nack { ack_seq, code } => {
+                out.extend_from_slice(&ack_seq.to_le_bytes());
+                out.push(*code);
+            }
+            Message::Hello => (),
+            Message::Identity(identity) => identity.encode(out),
+            Message::StateCommand(state) => out.push((*state).into()),
+            Message::StateAck { ack_seq, state } => {
+                out.extend_from_slice(&ack_seq.to_le_bytes());
+                out.push((*state).into());
+            }
+            Message::TimeRequest { ground_us } => out.extend_from_slice(&ground_us.to_le_bytes()),
+            Message::TimeReply { ground_us, robot_rx_us, robot_tx_us } => {
+                for stamp in [ground_us, robot_rx_us, robot_tx_us] {
+                    out.extend_from_slice(&stamp.to_le_bytes());
+                }
+            }
+            Message::Telemetry { robot_us, telemetry } => {
+                out.extend_from_slice(&robot_us.to_le_bytes());
+                telemetry.encode(out);
+            }
+            Message::Log { robot_us, level, tag, text } => {
+                out.extend_from_slice(&robot_us.to_le_bytes());
+                out.push((*level).into());
+                encode_str(tag, out);
+                out.extend_from_slice(text.as_bytes());
+            }
+            Message::Drive(command) => command.encode(out),
+            Message::BootEnter { base_address } => {
+                if let Some(base_address) = base_address {
+                    out.extend_from_slice(&base_address.to_le_bytes());
+                }
+            }
+            Message::Reboot | Message::ParamList | Message::ParamSave => (),
+            Message::BootReady { max_chunk } => out.extend_from_slice(&max_chunk.to_le_bytes()),
+            Message::FlashWrite { offset, data } => {
+                out.extend_from_slice(&offset.to_le_bytes());
+                out.extend_from_slice(data);
+            }
+            Message::FlashAck { offset } => out.extend_from_slice(&offset.to_le_bytes()),
+            Message::FlashVerify { length, crc } => {
+                out.extend_from_slice(&length.to_le_bytes());
+                out.extend_from_slice(&crc.to_le_bytes());
+            }
+            Message::FlashVerified { crc } => out.extend_from_slice(&crc.to_le_bytes()),
+            Message::ParamInfo { count, info } => {
+                out.extend_from_slice(&count.to_le_bytes());
+                info.encode(out);
+            }
+            Message::ParamGet { index } => out.extend_from_slice(&index.to_le_bytes()),
+            Message::ParamSet { index, value } => {
+                out.extend_from_slice(&index.to_le_bytes());
+                value.encode(out);
+            }
+            Message::ParamValue { ack_seq, index, value } => {
+                out.extend_from_slice(&ack_seq.to_le_bytes());
+                out.extend_from_slice(&index.to_le_bytes());
+                value.encode(out);
+            }
+            Message::ParamSaved { ack_seq } => out.extend_from_slice(&ack_seq.to_le_bytes()),
+        }
+    }
+
+    pub(super) fn decode(id: MessageId, payload: &[u8]) -> Result<Self, ProtocolError> {
+        let bad_payload = || ProtocolError::BadPayload(id);
+        match id {
+            MessageId::Heartbeat | MessageId::HeartbeatAck => {
+                let stamp = u32::from_le_bytes(payload.try_into().map_err(|_| bad_payload())?);
+                if id == MessageId::Heartbeat {
+                    Ok(Message::Heartbeat(stamp))
+                } else {
+                    Ok(Message::HeartbeatAck(stamp))
+                }
+            }
+            MessageId::Nack => {
+                let [seq_lo, seq_hi, code] = payload else {
+                    return Err(bad_payload());
+                };
+                Ok(Message::Nack { ack_seq: u16::from_le_bytes([*seq_lo, *seq_hi]), code: *code })
+            }
+            MessageId::Hello => payload.is_empty().then_some(Message::Hello).ok_or_else(bad_payload),
+            MessageId::Identity => RobotIdentity::decode(payload).map(Message::Identity).ok_or_else(bad_payload),
+            MessageId::StateCommand => {
+                let [byte] = payload else {
+                    return Err(bad_payload());
+                };
+                let state = DriverState::try_from(*byte).map_err(|_| bad_payload())?;
+                Ok(Message::StateCommand(state))
+            }
+            MessageId::StateAck => {
+                let [seq_lo, seq_hi, byte] = payload else {
+                    return Err(bad_payload());
+                };
+                let state = DriverState::try_from(*byte).map_err(|_| bad_payload())?;
+                Ok(Message::StateAck { ack_seq: u16::from_le_bytes([*seq_lo, *seq_hi]), state })
+            }
+            MessageId::TimeRequest => {
+                let ground_us = u64::from_le_bytes(payload.try_into().map_err(|_| bad_payload())?);
+                Ok(Message::TimeRequest { ground_us })
+            }
+            MessageId::TimeReply => {
+                if payload.len() != 24 {
+                    return Err(bad_payload());
+                }
+                let stamp = |index: usize| u64::from_le_bytes(payload[index * 8..index * 8 + 8].try_into().unwrap());
+                Ok(Message::TimeReply { ground_us: stamp(0), robot_rx_us: stamp(1), robot_tx_us: stamp(2) })
+            }
+            MessageId::Telemetry => {
+                if payload.len() < 8 {
+                    return Err(bad_payload());
+                }
+                let (robot_us, telemetry) = payload.split_at(8);
+                let telemetry = RobotTelemetry::decode(telemetry).ok_or_else(bad_payload)?;
+                Ok(Message::Telemetry { robot_us: u64::from_le_bytes(robot_us.try_into().unwrap()), telemetry })
+            }
+            MessageId::Log => {
+                if payload.len() < 9 {
+                    return Err(bad_payload());
+                }
+                let (robot_us, rest) = payload.split_at(8);
+                let level = LogLevel::try_from(rest[0]).map_err(|_| bad_payload())?;
+                let (tag, text) = decode_str(&rest[1..]).ok_or_else(bad_payload)?;
+                // Firmware text is shown as-is, so a stray byte shouldn't cost the whole line.
+                let text = String::from_utf8_lossy(text).into_owned();
+                Ok(Message::Log { robot_us: u64::from_le_bytes(robot_us.try_into().unwrap()), level, tag, text })
+            }
+            MessageId::Drive => DriveCommand::decode(payload).map(Message::Drive).ok_or_else(bad_payload),
+            MessageId::BootEnter => match payload.len() {
+                0 => Ok(Message::BootEnter { base_address: None }),
+                4 => Ok(Message::BootEnter { base_address: Some(u32::from_le_bytes(payload.try_into().unwrap())) }),
+                _ => Err(bad_payload()),
+            },
+            MessageId::Reboot => payload.is_empty().then_some(Message::Reboot).ok_or_else(bad_payload),
+            MessageId::BootReady => {
+                let max_chunk = u16::from_le_bytes(payload.try_into().map_err(|_| bad_payload())?);
+                Ok(Message::BootReady { max_chunk })
+            }
+            MessageId::FlashWrite => {
+                if payload.len() < 4 {
+                    return Err(bad_payload());
+                }
+                let (offset, data) = payload.split_at(4);
+                Ok(Message::FlashWrite { offset: u32::from_le_bytes(offset.try_into().unwrap()), data: data.to_vec() })
+            }
+            MessageId::FlashAck => {
+                let offset = u32::from_le_bytes(payload.try_into().map_err(|_| bad_payload())?);
+                Ok(Message::FlashAck { offset })
+            }
+            MessageId::FlashVerify => {
+                if payload.len() != 8 {
+                    return Err(bad_payload());
+                }
+                let (length, crc) = payload.split_at(4);
+                Ok(Message::FlashVerify {
+                    length: u32::from_le_bytes(length.try_into().unwrap()),
+                    crc: u32::from_le_bytes(crc.try_into().unwrap()),
+                })
+            }
+            MessageId::FlashVerified => {
+                let crc = u32::from_le_bytes(payload.try_into().map_err(|_| bad_payload())?);
+                Ok(Message::FlashVerified { crc })
+            }
+            MessageId::ParamList => payload.is_empty().then_some(Message::ParamList).ok_or_else(bad_payload),
+            MessageId::ParamSave => payload.is_empty().then_some(Message::ParamSave).ok_or_else(bad_payload),
+            MessageId::ParamInfo => {
+                let [count_lo, count_hi, rest @ ..] = payload else {
+                    return Err(bad_payload());
+                };
+                let info = ParamInfo::decode(rest).ok_or_else(bad_payload)?;
+                Ok(Message::ParamInfo { count: u16::from_le_bytes([*count_lo, *count_hi]), info })
+            }
+            MessageId::ParamGet => {
+                let index = u16::from_le_bytes(payload.try_into().map_err(|_| bad_payload())?);
+                Ok(Message::ParamGet { index })
+            }
+            MessageId::ParamSet => {
+                let [index_lo, index_hi, rest @ ..] = payload else {
+                    return Err(bad_payload());
+                };
+                match ParamValue::decode(rest) {
+                    Some((value, [])) => Ok(Message::ParamSet { index: u16::from_le_bytes([*index_lo, *index_hi]), value }),
+                    _ => Err(bad_payload()),
+                }
+            }
+            MessageId::ParamValue => {
+                let [seq_lo, seq_hi, index_lo, index_hi, rest @ ..] = payload else {
+                    return Err(bad_payload());
+                };
+                match ParamValue::decode(rest) {
+                    Some((value, [])) => Ok(Message::ParamValue {
+                        ack_seq: u16::from_le_bytes([*seq_lo, *seq_hi]),
+                        index: u16::from_le_bytes([*index_lo, *index_hi]),
+                        value,
+                    }),
+                    _ => Err(bad_payload()),
+                }
+            }
+            MessageId::ParamSaved => {
+                let ack_seq = u16::from_le_bytes(payload.try_into().map_err(|_| bad_payload())?);
+                Ok(Message::ParamSaved { ack_seq })
+            }
+        }
+    }
+}
+
+/// A message together with the sender's sequence number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet {
+    pub seq: u16,
+    pub message: Message,
+}
+
+impl Packet {
+    pub fn new(seq: u16, message: Message) -> Self {
+        Self { seq, message }
+    }
+}
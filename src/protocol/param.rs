@@ -0,0 +1,122 @@
+use std::fmt;
+
+use super::message::{decode_str, encode_str};
+
+/// A robot parameter value; every kind travels as a tag byte and four bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+}
+
+impl ParamValue {
+    const ENCODED_LEN: usize = 5;
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParamValue::Bool(_) => "bool",
+            ParamValue::Int(_) => "int",
+            ParamValue::Float(_) => "float",
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            ParamValue::Bool(value) => *value as u8 as f64,
+            ParamValue::Int(value) => *value as f64,
+            ParamValue::Float(value) => *value as f64,
+        }
+    }
+
+    /// Parses `text` as a value of the same kind as `self`.
+    pub fn parse_like(&self, text: &str) -> Result<ParamValue, String> {
+        let text = text.trim();
+        match self {
+            ParamValue::Bool(_) => match text.to_ascii_lowercase().as_str() {
+                "true" | "1" | "on" | "yes" => Ok(ParamValue::Bool(true)),
+                "false" | "0" | "off" | "no" => Ok(ParamValue::Bool(false)),
+                _ => Err(format!("'{}' is not a bool", text)),
+            },
+            ParamValue::Int(_) => text.parse().map(ParamValue::Int).map_err(|_| format!("'{}' is not an integer", text)),
+            ParamValue::Float(_) => text.parse().map(ParamValue::Float).map_err(|_| format!("'{}' is not a number", text)),
+        }
+    }
+
+    pub(super) fn encode(&self, out: &mut Vec<u8>) {
+        let (tag, bytes) = match self {
+            ParamValue::Bool(value) => (0, (*value as u32).to_le_bytes()),
+            ParamValue::Int(value) => (1, value.to_le_bytes()),
+            ParamValue::Float(value) => (2, value.to_le_bytes()),
+        };
+        out.push(tag);
+        out.extend_from_slice(&bytes);
+    }
+
+    pub(super) fn decode(payload: &[u8]) -> Option<(Self, &[u8])> {
+        if payload.len() < Self::ENCODED_LEN {
+            return None;
+        }
+        let (value, rest) = payload.split_at(Self::ENCODED_LEN);
+        let bytes: [u8; 4] = value[1..].try_into().ok()?;
+        let value = match value[0] {
+            0 => ParamValue::Bool(u32::from_le_bytes(bytes) != 0),
+            1 => ParamValue::Int(i32::from_le_bytes(bytes)),
+            2 => ParamValue::Float(f32::from_le_bytes(bytes)),
+            _ => return None,
+        };
+        Some((value, rest))
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamValue::Bool(value) => write!(f, "{}", value),
+            ParamValue::Int(value) => write!(f, "{}", value),
+            ParamValue::Float(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Everything the robot reports about one of its parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamInfo {
+    pub index: u16,
+    pub name: String,
+    pub value: ParamValue,
+    pub min: ParamValue,
+    pub max: ParamValue,
+    pub default: ParamValue,
+}
+
+impl ParamInfo {
+    pub fn in_range(&self, value: &ParamValue) -> bool {
+        (self.min.as_f64()..=self.max.as_f64()).contains(&value.as_f64())
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.value == self.default
+    }
+
+    pub(super) fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.index.to_le_bytes());
+        encode_str(&self.name, out);
+        for value in [self.value, self.min, self.max, self.default] {
+            value.encode(out);
+        }
+    }
+
+    pub(super) fn decode(payload: &[u8]) -> Option<Self> {
+        if payload.len() < 2 {
+            return None;
+        }
+        let (index, rest) = payload.split_at(2);
+        let (name, rest) = decode_str(rest)?;
+        let (value, rest) = ParamValue::decode(rest)?;
+        let (min, rest) = ParamValue::decode(rest)?;
+        let (max, rest) = ParamValue::decode(rest)?;
+        let (default, rest) = ParamValue::decode(rest)?;
+        rest.is_empty().then_some(Self { index: u16::from_le_bytes([index[0], index[1]]), name, value, min, max, default })
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+
+/// Faults the robot raises in its telemetry, one bit each.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultFlags(pub u16);
+
+impl FaultFlags {
+    pub const OVERCURRENT: u16 = 1 << 0;
+    pub const UNDERVOLTAGE: u16 = 1 << 1;
+    pub const OVERTEMPERATURE: u16 = 1 << 2;
+    pub const ENCODER: u16 = 1 << 3;
+    pub const MOTOR_DRIVER: u16 = 1 << 4;
+    /// The robot's own e-stop button or loop is open.
+    pub const ESTOP_INPUT: u16 = 1 << 5;
+    pub const WATCHDOG: u16 = 1 << 6;
+
+    const NAMES: [(u16, &'static str); 7] = [
+        (Self::OVERCURRENT, "overcurrent"),
+        (Self::UNDERVOLTAGE, "undervoltage"),
+        (Self::OVERTEMPERATURE, "overtemperature"),
+        (Self::ENCODER, "encoder"),
+        (Self::MOTOR_DRIVER, "motor driver"),
+        (Self::ESTOP_INPUT, "e-stop input"),
+        (Self::WATCHDOG, "watchdog"),
+    ];
+
+    pub fn contains(&self, flag: u16) -> bool {
+        self.0 & flag == flag
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for FaultFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+        let mut names: Vec<String> = Self::NAMES.iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| name.to_string())
+            .collect();
+        let known = Self::NAMES.iter().fold(0, |known, (flag, _)| known | flag);
+        if self.0 & !known != 0 {
+            names.push(format!("unknown {:#06x}", self.0 & !known));
+        }
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+/// One motor's share of a telemetry frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MotorTelemetry {
+    pub current_a: f32,
+    pub encoder_ticks: i32,
+    pub temperature_c: f32,
+}
+
+/// The robot's health as decoded from a telemetry frame.
+///
+/// On the wire: battery in mV (`u16`), MCU temperature in 0.1 °C (`i16`), fault
+/// flags (`u16`), a motor count (`u8`), then per motor its current in mA (`i16`),
+/// encoder ticks (`i32`) and temperature in 0.1 °C (`i16`), and last the heading in
+/// 0.1° (`i16`) from robots with a gyro, all little-endian.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotTelemetry {
+    pub battery_v: f32,
+    pub mcu_temperature_c: f32,
+    pub faults: FaultFlags,
+    pub motors: Vec<MotorTelemetry>,
+    /// Degrees clockwise from where the robot faced when its gyro started.
+    pub heading_deg: Option<f32>,
+}
+
+impl RobotTelemetry {
+    const HEADER_LEN: usize = 7;
+    const MOTOR_LEN: usize = 8;
+
+    pub(super) fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&((self.battery_v * 1000.0).round().clamp(0.0, u16::MAX as f32) as u16).to_le_bytes());
+        out.extend_from_slice(&deci(self.mcu_temperature_c).to_le_bytes());
+        out.extend_from_slice(&self.faults.0.to_le_bytes());
+        out.push(self.motors.len().min(u8::MAX as usize) as u8);
+        for motor in self.motors.iter().take(u8::MAX as usize) {
+            let milliamps = (motor.current_a * 1000.0).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            out.extend_from_slice(&milliamps.to_le_bytes());
+            out.extend_from_slice(&motor.encoder_ticks.to_le_bytes());
+            out.extend_from_slice(&deci(motor.temperature_c).to_le_bytes());
+        }
+        if let Some(heading) = self.heading_deg {
+            out.extend_from_slice(&deci(heading).to_le_bytes());
+        }
+    }
+
+    pub(super) fn decode(payload: &[u8]) -> Option<Self> {
+        if payload.len() < Self::HEADER_LEN {
+            return None;
+        }
+        let (header, rest) = payload.split_at(Self::HEADER_LEN);
+        let motors_len = header[6] as usize * Self::MOTOR_LEN;
+        if rest.len() < motors_len {
+            return None;
+        }
+        let (motors, heading) = rest.split_at(motors_len);
+        let heading_deg = match heading {
+            [] => None,
+            [lo, hi] => Some(i16::from_le_bytes([*lo, *hi]) as f32 / 10.0),
+            _ => return None,
+        };
+        Some(Self {
+            battery_v: u16::from_le_bytes([header[0], header[1]]) as f32 / 1000.0,
+            mcu_temperature_c: i16::from_le_bytes([header[2], header[3]]) as f32 / 10.0,
+            faults: FaultFlags(u16::from_le_bytes([header[4], header[5]])),
+            motors: motors.chunks_exact(Self::MOTOR_LEN).map(|motor| MotorTelemetry {
+                current_a: i16::from_le_bytes([motor[0], motor[1]]) as f32 / 1000.0,
+                encoder_ticks: i32::from_le_bytes([motor[2], motor[3], motor[4], motor[5]]),
+                temperature_c: i16::from_le_bytes([motor[6], motor[7]]) as f32 / 10.0,
+            }).collect(),
+            heading_deg,
+        })
+    }
+}
+
+/// Tenths of a degree, as temperatures travel.
+fn deci(celsius: f32) -> i16 {
+    (celsius * 10.0).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
@@ -1,5 +1,29 @@
+mod clock_sync;
+mod command;
 mod controller_task;
+mod discovery;
+mod driver_registry;
 mod driver_task;
+mod firmware;
+mod link;
+mod link_stats;
+mod params;
+mod raw_chunk;
+mod state_machine;
+mod telemetry;
+mod transport;
 
-pub use controller_task::ControllerTask;
-pub use driver_task::{DriverTask, DriverEvent};
\ No newline at end of file
+pub use clock_sync::{ClockEstimate, ClockSync};
+pub use command::{CommandPhase, CommandPolicy, CommandStatus};
+pub use controller_task::{ControllerError, ControllerStatus, ControllerTask};
+pub use discovery::{probe, PortInfo, ProbeResult, PROBE_TIMEOUT};
+pub use driver_registry::DriverRegistry;
+pub use driver_task::{DriverError, DriverTask, DriverEvent, LinkStatus};
+pub use firmware::{FirmwareError, FirmwareImage, UploadProgress, UploadStage};
+pub use link::Link;
+pub use link_stats::LinkStats;
+pub use params::{ParamEvent, ParamRequest};
+pub use raw_chunk::{RawChunk, RawDirection, TimeOfDay};
+pub use state_machine::{DriverState, StateMachine, Transition};
+pub use telemetry::{LogEntry, TelemetrySample};
+pub use transport::{transport_for, DriverPort, LineSetting, LineSettings, NetProtocol, TcpTransport, Transport, UdpTransport};
@@ -0,0 +1,119 @@
+use std::{collections::VecDeque, fmt, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+/// Number of recent exchanges the estimate is fitted over.
+const SYNC_WINDOW: usize = 32;
+
+/// Ground-station time as microseconds since the Unix epoch, the unit sent on the wire.
+pub fn ground_now_us() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0)
+}
+
+/// One request/reply exchange, reduced to when it happened, the offset it saw and its
+/// round-trip delay.
+#[derive(Debug, Clone, Copy)]
+struct SyncSample {
+    ground_us: f64,
+    offset_us: f64,
+    delay_us: f64,
+}
+
+/// Mapping from the robot's clock to ground-station time.
+///
+/// The robot clock is modelled as `robot = ground + offset + drift * (ground - reference)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockEstimate {
+    /// Robot minus ground time at `reference_us`, in microseconds.
+    pub offset_us: f64,
+    /// Ground time, in microseconds since the epoch, the offset applies at.
+    pub reference_us: f64,
+    /// How fast the robot clock runs relative to ground, as a fraction (1e-6 is 1 ppm).
+    pub drift: f64,
+    /// Round-trip delay of the best exchange in the window.
+    pub delay: Duration,
+    pub samples: usize,
+}
+
+impl ClockEstimate {
+    pub fn drift_ppm(&self) -> f64 {
+        self.drift * 1e6
+    }
+
+    /// Ground-station time at which the robot's clock read `robot_us`.
+    pub fn to_ground(&self, robot_us: u64) -> SystemTime {
+        let ground_us = (robot_us as f64 - self.offset_us + self.drift * self.reference_us) / (1.0 + self.drift);
+        UNIX_EPOCH + Duration::from_micros(ground_us.max(0.0) as u64)
+    }
+}
+
+impl fmt::Display for ClockEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "offset {:.3} s, drift {:+.1} ppm, best RTT {:.1} ms ({} samples)",
+            self.offset_us / 1e6, self.drift_ppm(), self.delay.as_secs_f64() * 1000.0, self.samples)
+    }
+}
+
+/// NTP-style estimator fed with timestamped exchanges over the driver link.
+#[derive(Debug, Default)]
+pub struct ClockSync {
+    samples: VecDeque<SyncSample>,
+    estimate: Option<ClockEstimate>,
+}
+
+impl ClockSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an exchange: `t1` ground send, `t2` robot receive, `t3` robot send, `t4`
+    /// ground receive, all in microseconds on their own clocks.
+    pub fn add_exchange(&mut self, t1: u64, t2: u64, t3: u64, t4: u64) -> Option<ClockEstimate> {
+        let (t1, t2, t3, t4) = (t1 as f64, t2 as f64, t3 as f64, t4 as f64);
+        let delay_us = (t4 - t1) - (t3 - t2);
+        if delay_us < 0.0 {
+            // Robot reported time going backwards or a stale echo; nothing to learn.
+            return self.estimate;
+        }
+        self.samples.push_back(SyncSample {
+            ground_us: (t1 + t4) / 2.0,
+            offset_us: ((t2 - t1) + (t3 - t4)) / 2.0,
+            delay_us,
+        });
+        if self.samples.len() > SYNC_WINDOW {
+            self.samples.pop_front();
+        }
+        self.estimate = self.fit();
+        self.estimate
+    }
+
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        self.estimate
+    }
+
+    /// Fits offset and drift over the faster half of the window, since queueing delay
+    /// only ever adds error.
+    fn fit(&self) -> Option<ClockEstimate> {
+        let mut best: Vec<SyncSample> = self.samples.iter().copied().collect();
+        best.sort_by(|a, b| a.delay_us.total_cmp(&b.delay_us));
+        let delay = Duration::from_micros(best.first()?.delay_us as u64);
+        best.truncate(best.len().div_ceil(2));
+
+        let count = best.len() as f64;
+        let mean_ground = best.iter().map(|sample| sample.ground_us).sum::<f64>() / count;
+        let mean_offset = best.iter().map(|sample| sample.offset_us).sum::<f64>() / count;
+        let spread = best.iter().map(|sample| (sample.ground_us - mean_ground).powi(2)).sum::<f64>();
+        let drift = if spread > 0.0 {
+            best.iter()
+                .map(|sample| (sample.ground_us - mean_ground) * (sample.offset_us - mean_offset))
+                .sum::<f64>() / spread
+        } else {
+            0.0
+        };
+        Some(ClockEstimate {
+            offset_us: mean_offset,
+            reference_us: mean_ground,
+            drift,
+            delay,
+            samples: self.samples.len(),
+        })
+    }
+}
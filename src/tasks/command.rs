@@ -0,0 +1,81 @@
+use std::{fmt, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::protocol::Packet;
+
+use super::DriverState;
+
+/// How long to wait for an acknowledgement and how often to resend before giving up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandPolicy {
+    pub timeout: Duration,
+    pub retries: u32,
+}
+
+impl Default for CommandPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_millis(250),
+            retries: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandPhase {
+    Pending,
+    Acked,
+    Failed(String),
+}
+
+/// Progress of a state-change command, reported to the UI on every update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandStatus {
+    pub seq: u16,
+    pub state: DriverState,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub phase: CommandPhase,
+}
+
+impl fmt::Display for CommandStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} {}: ", self.seq, self.state)?;
+        match &self.phase {
+            CommandPhase::Pending => write!(f, "waiting for ack (attempt {}/{})", self.attempt, self.max_attempts),
+            CommandPhase::Acked => write!(f, "acknowledged"),
+            CommandPhase::Failed(reason) => write!(f, "failed: {}", reason),
+        }
+    }
+}
+
+/// A command sent to the robot that has not been acknowledged yet.
+#[derive(Debug)]
+pub struct PendingCommand {
+    pub packet: Packet,
+    pub state: DriverState,
+    pub attempt: u32,
+    pub deadline: Instant,
+}
+
+impl PendingCommand {
+    pub fn new(packet: Packet, state: DriverState, policy: &CommandPolicy) -> Self {
+        Self {
+            packet,
+            state,
+            attempt: 1,
+            deadline: Instant::now() + policy.timeout,
+        }
+    }
+
+    pub fn status(&self, policy: &CommandPolicy, phase: CommandPhase) -> CommandStatus {
+        CommandStatus {
+            seq: self.packet.seq,
+            state: self.state,
+            attempt: self.attempt,
+            max_attempts: policy.retries + 1,
+            phase,
+        }
+    }
+}
@@ -1,50 +1,221 @@
+use std::{fmt, sync::mpsc as std_mpsc, thread, time::Duration};
 
-//use futures::{FutureExt, StreamExt};
-use gilrs::{Gilrs, Event as GamepadEvent};
+use gilrs::{Event as GamepadEvent, EventType as GamepadEventType, Gamepad, GamepadId, Gilrs};
 use tokio::sync::mpsc;
 use crate::event::Event;
 
+/// Longest the input thread blocks on gilrs before looking for requests.
+const REQUEST_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+pub enum ControllerError {
+    NotStarted,
+    Input(String),
+}
+
+impl fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControllerError::NotStarted => write!(f, "controller input is not running"),
+            ControllerError::Input(e) => write!(f, "gamepad input unavailable: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ControllerError {}
+
+/// Where the selected controller stands; reported whenever it changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControllerStatus {
+    /// Selected but not plugged in; picked up as soon as it appears.
+    Waiting(String),
+    /// Plugged in, with the UUID its binding profile is chosen by.
+    Connected { name: String, uuid: String },
+    /// Unplugged; the same pad is picked up again when it returns.
+    Disconnected(String),
+}
+
+impl fmt::Display for ControllerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControllerStatus::Waiting(name) => write!(f, "Waiting for {}", name),
+            ControllerStatus::Connected { name, .. } => write!(f, "{} connected", name),
+            ControllerStatus::Disconnected(name) => write!(f, "{} disconnected, waiting for it to return", name),
+        }
+    }
+}
+
+/// The physical pad the operator picked, recognised again after a re-plug by its
+/// UUID once seen, or by name before that.
+#[derive(Debug, Clone)]
+struct WantedPad {
+    name: String,
+    uuid: Option<[u8; 16]>,
+}
+
+impl WantedPad {
+    fn matches(&self, gamepad: &Gamepad) -> bool {
+        match self.uuid {
+            Some(uuid) => gamepad.uuid() == uuid && gamepad.name() == self.name,
+            None => gamepad.name() == self.name,
+        }
+    }
+
+    fn claim(&mut self, gamepad: &Gamepad) {
+        // Pads without a mapping report an all-zero UUID, which says nothing.
+        if gamepad.uuid() != [0; 16] {
+            self.uuid = Some(gamepad.uuid());
+        }
+    }
+}
+
+/// Asks for the input service thread.
+#[derive(Debug)]
+enum InputRequest {
+    /// Reply with the names of every pad plugged in, as [`Event::Controllers`].
+    List,
+    /// Forward events from the named pad, instead of any followed before.
+    Follow(String),
+    Shutdown,
+}
+
+/// The selected pad and, while it is plugged in, its gilrs id.
+#[derive(Debug)]
+struct Followed {
+    wanted: WantedPad,
+    current: Option<GamepadId>,
+}
+
+/// Handle on the one thread that owns gilrs: it lists controllers on request and
+/// forwards events from the followed one, blocking between them.
 #[derive(Debug)]
 pub struct ControllerTask {
-    sender: Option<mpsc::UnboundedSender<Event>>,
-    controller: Option<String>,
-    task: Option<tokio::task::JoinHandle<()>>,
+    requests: Option<std_mpsc::Sender<InputRequest>>,
+    thread: Option<thread::JoinHandle<()>>,
 }
 
 impl ControllerTask {
     pub fn new() -> Self {
         Self {
-            sender: None,
-            controller: None,
-            task: None,
+            requests: None,
+            thread: None,
         }
     }
 
-    pub fn add_sender(&mut self, sender: &mpsc::UnboundedSender<Event>){
-        self.sender = Some(sender.clone());
+    /// Starts the input thread, which reports to `sender` until shut down.
+    pub fn start(&mut self, sender: mpsc::UnboundedSender<Event>) -> Result<(), ControllerError> {
+        self.shutdown();
+        let (requests, receiver) = std_mpsc::channel();
+        let (ready_tx, ready_rx) = std_mpsc::channel();
+        // gilrs isn't Send, so it is created on the thread that uses it.
+        let thread = thread::spawn(move || {
+            match Gilrs::new() {
+                Ok(gilrs) => {
+                    let _ = ready_tx.send(Ok(()));
+                    run(gilrs, receiver, sender);
+                },
+                Err(e) => {
+                    let _ = ready_tx.send(Err(ControllerError::Input(e.to_string())));
+                },
+            }
+        });
+        ready_rx.recv().unwrap_or(Err(ControllerError::NotStarted))?;
+        self.requests = Some(requests);
+        self.thread = Some(thread);
+        Ok(())
+    }
+
+    /// Asks for the controller list; it arrives as [`Event::Controllers`].
+    pub fn list(&self) -> Result<(), ControllerError> {
+        self.request(InputRequest::List)
+    }
+
+    /// Follows the named pad; one that is not plugged in yet is waited for rather than refused.
+    pub fn follow(&self, controller: String) -> Result<(), ControllerError> {
+        self.request(InputRequest::Follow(controller))
+    }
+
+    fn request(&self, request: InputRequest) -> Result<(), ControllerError> {
+        self.requests.as_ref()
+            .ok_or(ControllerError::NotStarted)?
+            .send(request)
+            .map_err(|_| ControllerError::NotStarted)
+    }
+
+    /// Stops the input thread and waits for it to let go of the devices.
+    pub fn shutdown(&mut self) {
+        if let Some(requests) = self.requests.take() {
+            let _ = requests.send(InputRequest::Shutdown);
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
     }
+}
 
-    pub fn add_controller(&mut self, controller:String) {
-        self.controller = Some(controller);
+impl Drop for ControllerTask {
+    fn drop(&mut self) {
+        self.shutdown();
     }
+}
 
-    pub fn add_task(&mut self){
-        let _sender = self.sender.as_mut().unwrap().clone();
-        let controller_name = self.controller.as_ref().unwrap().clone();
-        let mut gilrs = Gilrs::new().unwrap();
-        let _controller_id = gilrs.gamepads().filter(|(_, gamepad)| {
-            controller_name.eq(gamepad.name())})
-            .map(|(id, _)| id)
-            .next().unwrap();
-        self.task = Some(
-            tokio::spawn(async move {
-                loop {
-                    while let Some(GamepadEvent { id, event, time, .. }) = gilrs.next_event() {
-                        if id == _controller_id {
-                            _sender.send(Event::Controller(event));
-                        }
-                    }
-                }
-        }));
+fn run(mut gilrs: Gilrs, requests: std_mpsc::Receiver<InputRequest>, sender: mpsc::UnboundedSender<Event>) {
+    let mut followed: Option<Followed> = None;
+    while !sender.is_closed() {
+        loop {
+            match requests.try_recv() {
+                Ok(InputRequest::List) => {
+                    let names = gilrs.gamepads().map(|(_, gamepad)| gamepad.name().to_string()).collect();
+                    let _ = sender.send(Event::Controllers(names));
+                },
+                Ok(InputRequest::Follow(name)) => followed = Some(follow(&gilrs, name, &sender)),
+                Ok(InputRequest::Shutdown) | Err(std_mpsc::TryRecvError::Disconnected) => return,
+                Err(std_mpsc::TryRecvError::Empty) => break,
+            }
+        }
+        let Some(GamepadEvent { id, event, time, .. }) = gilrs.next_event_blocking(Some(REQUEST_POLL)) else {
+            continue;
+        };
+        let Some(Followed { wanted, current }) = followed.as_mut() else {
+            continue;
+        };
+        let ours = match event {
+            GamepadEventType::Connected if current.is_none() && wanted.matches(&gilrs.gamepad(id)) => {
+                let gamepad = gilrs.gamepad(id);
+                wanted.claim(&gamepad);
+                *current = Some(id);
+                let _ = sender.send(Event::ControllerStatus(ControllerStatus::Connected { name: wanted.name.clone(), uuid: uuid_hex(&gamepad) }));
+                true
+            },
+            GamepadEventType::Disconnected if *current == Some(id) => {
+                *current = None;
+                let _ = sender.send(Event::ControllerStatus(ControllerStatus::Disconnected(wanted.name.clone())));
+                true
+            },
+            _ => *current == Some(id),
+        };
+        if ours {
+            let _ = sender.send(Event::Controller(event, time));
+        }
     }
-}
\ No newline at end of file
+}
+
+fn follow(gilrs: &Gilrs, name: String, sender: &mpsc::UnboundedSender<Event>) -> Followed {
+    let mut wanted = WantedPad { name, uuid: None };
+    let found = gilrs.gamepads().find(|(_, gamepad)| wanted.matches(gamepad));
+    let status = match &found {
+        Some((_, gamepad)) => {
+            wanted.claim(gamepad);
+            ControllerStatus::Connected { name: wanted.name.clone(), uuid: uuid_hex(gamepad) }
+        },
+        None => ControllerStatus::Waiting(wanted.name.clone()),
+    };
+    let current = found.map(|(id, _)| id);
+    let _ = sender.send(Event::ControllerStatus(status));
+    Followed { wanted, current }
+}
+
+/// The pad's UUID as the bindings file names it, in lowercase hex.
+fn uuid_hex(gamepad: &Gamepad) -> String {
+    gamepad.uuid().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
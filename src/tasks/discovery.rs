@@ -0,0 +1,116 @@
+use std::{fmt, fs, path::Path, time::Duration};
+
+use serialport::{available_ports, SerialPortType};
+use tokio::time;
+
+use crate::protocol::{FrameDecoder, Message, Packet, RobotIdentity};
+
+use super::{transport::open_link, DriverError, Link, Transport};
+
+/// Where udev keeps stable, per-adapter names for USB serial ports.
+const BY_ID_DIR: &str = "/dev/serial/by-id";
+/// How long a port has to identify itself before the scan gives up on it.
+pub const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A serial port as the OS enumerates it, with what it knows about the hardware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortInfo {
+    pub name: String,
+    pub port_type: SerialPortType,
+    /// For a `/dev/serial/by-id` link, the device it points at.
+    pub link_to: Option<String>,
+}
+
+impl PortInfo {
+    /// Enumerated ports followed by the by-id links to them, which survive re-plugging.
+    pub fn list() -> Result<Vec<Self>, DriverError> {
+        let mut ports: Vec<Self> = available_ports()?
+            .into_iter()
+            .map(|info| Self { name: info.port_name, port_type: info.port_type, link_to: None })
+            .collect();
+        let links: Vec<Self> = fs::read_dir(BY_ID_DIR)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let target = fs::canonicalize(entry.path()).ok()?.to_string_lossy().into_owned();
+                let port = ports.iter().find(|port| port.name == target)?;
+                Some(Self {
+                    name: entry.path().to_string_lossy().into_owned(),
+                    port_type: port.port_type.clone(),
+                    link_to: Some(target),
+                })
+            })
+            .collect();
+        ports.extend(links);
+        Ok(ports)
+    }
+
+    /// One line for the port list, e.g. `ttyUSB0 USB 0403:6001 FTDI FT232R #A10K3`.
+    pub fn label(&self) -> String {
+        let name = Path::new(&self.name).file_name().and_then(|name| name.to_str()).unwrap_or(&self.name);
+        match &self.link_to {
+            Some(target) => format!("{} -> {}", name, Path::new(target).file_name().and_then(|name| name.to_str()).unwrap_or(target)),
+            None => format!("{} {}", name, PortKind(&self.port_type)),
+        }
+    }
+}
+
+/// Describes how a port is attached, with the USB identity when there is one.
+struct PortKind<'a>(&'a SerialPortType);
+
+impl fmt::Display for PortKind<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            SerialPortType::UsbPort(usb) => {
+                write!(f, "USB {:04x}:{:04x}", usb.vid, usb.pid)?;
+                for text in [&usb.manufacturer, &usb.product].into_iter().flatten() {
+                    write!(f, " {}", text)?;
+                }
+                if let Some(serial_number) = &usb.serial_number {
+                    write!(f, " #{}", serial_number)?;
+                }
+                Ok(())
+            },
+            SerialPortType::PciPort => write!(f, "PCI"),
+            SerialPortType::BluetoothPort => write!(f, "Bluetooth"),
+            SerialPortType::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// What a scan found on one port.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeResult {
+    pub port: String,
+    pub outcome: Result<RobotIdentity, String>,
+}
+
+/// Opens the transport, says hello and waits for the robot to identify itself.
+pub async fn probe(transport: &dyn Transport, timeout: Duration) -> Result<RobotIdentity, String> {
+    let mut link = open_link(transport).await.map_err(|e| e.to_string())?;
+    let answer = match link.send(Packet::new(0, Message::Hello).encode()) {
+        Ok(()) => identify(&mut link, timeout).await,
+        Err(e) => Err(e.to_string()),
+    };
+    // The port has to be free again for the driver that may open it next.
+    link.close().await;
+    answer
+}
+
+/// Waits for the robot's identity, skipping anything else on the port.
+async fn identify(link: &mut Link, timeout: Duration) -> Result<RobotIdentity, String> {
+    let mut decoder = FrameDecoder::new();
+    let answer = time::timeout(timeout, async {
+        loop {
+            let bytes = link.recv().await.map_err(|e| e.to_string())?;
+            // Anything else on the port, including garbage from a non-robot, is skipped.
+            for result in decoder.decode(&bytes) {
+                if let Ok(Packet { message: Message::Identity(identity), .. }) = result {
+                    return Ok(identity);
+                }
+            }
+        }
+    });
+    answer.await.unwrap_or_else(|_| Err("no answer".to_string()))
+}
@@ -0,0 +1,196 @@
+use futures::future::join_all;
+use tokio::sync::mpsc;
+
+use crate::{event::Event, protocol::DriveCommand};
+
+use super::{discovery::{probe, PortInfo, ProbeResult, PROBE_TIMEOUT}, transport_for, DriverError, DriverState, FirmwareImage, ParamRequest, DriverTask, LineSetting, LineSettings};
+
+/// Runs one [`DriverTask`] per port or network endpoint, e.g. the main MCU and the arm controller.
+///
+/// Port, state and drive controls apply to the active driver; e-stop applies to all of them.
+#[derive(Debug, Default)]
+pub struct DriverRegistry {
+    event_sender: Option<mpsc::UnboundedSender<Event>>,
+    drivers: Vec<DriverTask>,
+    active: Option<usize>,
+    /// Line settings and drive rate given to drivers created after they were chosen.
+    line: LineSettings,
+    drive_rate_hz: Option<u32>,
+}
+
+impl DriverRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_sender(&mut self, event_sender: mpsc::UnboundedSender<Event>) {
+        for driver in self.drivers.iter_mut() {
+            driver.set_sender(event_sender.clone());
+        }
+        self.event_sender = Some(event_sender);
+    }
+
+    /// Starts the driver on `port`, a serial device or a `tcp://` or `udp://` endpoint,
+    /// creating it on first use. The first driver becomes active.
+    pub fn start(&mut self, port: String) -> Result<(), DriverError> {
+        let index = match self.find(&port) {
+            Some(index) => index,
+            None => {
+                let transport = transport_for(&port, self.line)?;
+                let mut driver = DriverTask::new(transport.short_name().to_string());
+                driver.set_line_settings(self.line);
+                if let Some(rate_hz) = self.drive_rate_hz {
+                    driver.set_drive_rate(rate_hz);
+                }
+                if let Some(event_sender) = self.event_sender.clone() {
+                    driver.set_sender(event_sender);
+                }
+                driver.set_transport(transport);
+                self.drivers.push(driver);
+                self.drivers.len() - 1
+            }
+        };
+        self.drivers[index].start_driver()?;
+        self.active.get_or_insert(index);
+        Ok(())
+    }
+
+    /// Stops the driver on `port`; it stays registered so it can be started again.
+    pub fn stop(&mut self, port: &str) -> Result<(), DriverError> {
+        let index = self.find(port).ok_or_else(|| DriverError::UnknownDriver(port.to_string()))?;
+        self.drivers[index].stop_driver()
+    }
+
+    /// Makes the driver on `port` the one that port, state and drive controls apply to.
+    /// The driver it replaces is stopped in place, as drive input no longer reaches it.
+    pub fn select(&mut self, port: &str) -> Result<(), DriverError> {
+        let index = self.find(port).ok_or_else(|| DriverError::UnknownDriver(port.to_string()))?;
+        if let Some(previous) = self.active.filter(|previous| *previous != index) {
+            self.drivers[previous].set_drive(DriveCommand::neutral());
+        }
+        self.active = Some(index);
+        Ok(())
+    }
+
+    /// Ports and endpoints of every registered driver, in the order they were added.
+    pub fn ports(&self) -> Vec<String> {
+        self.drivers.iter().filter_map(|driver| driver.port()).map(|port| port.name().to_string()).collect()
+    }
+
+    pub fn active_name(&self) -> Option<&str> {
+        self.active.map(|index| self.drivers[index].name())
+    }
+
+    fn active_mut(&mut self) -> Result<&mut DriverTask, DriverError> {
+        match self.active {
+            Some(index) => Ok(&mut self.drivers[index]),
+            None => Err(DriverError::NoPortSet),
+        }
+    }
+
+    fn find(&self, port: &str) -> Option<usize> {
+        self.drivers.iter().position(|driver| driver.port().is_some_and(|driver_port| driver_port.name() == port))
+    }
+
+    pub fn set_line(&mut self, setting: LineSetting) {
+        match self.active_mut() {
+            Ok(driver) => driver.set_line(setting),
+            Err(_) => self.line.apply(setting),
+        }
+    }
+
+    /// The active driver's line settings, or those the next driver will get.
+    pub fn line_settings(&self) -> LineSettings {
+        match self.active {
+            Some(index) => self.drivers[index].line_settings(),
+            None => self.line,
+        }
+    }
+
+    pub fn set_drive_rate(&mut self, rate_hz: u32) {
+        match self.active_mut() {
+            Ok(driver) => driver.set_drive_rate(rate_hz),
+            Err(_) => self.drive_rate_hz = Some(rate_hz),
+        }
+    }
+
+    pub fn request_state(&mut self, state: DriverState) -> Result<(), DriverError> {
+        self.active_mut()?.request_state(state)
+    }
+
+    pub fn reset(&mut self) -> Result<(), DriverError> {
+        self.active_mut()?.reset()
+    }
+
+    pub fn send_raw(&mut self, bytes: Vec<u8>) -> Result<(), DriverError> {
+        self.active_mut()?.send_raw(bytes)
+    }
+
+    pub fn upload_firmware(&mut self, image: FirmwareImage) -> Result<(), DriverError> {
+        self.active_mut()?.upload_firmware(image)
+    }
+
+    pub fn request_param(&mut self, request: ParamRequest) -> Result<(), DriverError> {
+        self.active_mut()?.request_param(request)
+    }
+
+    /// Hands the latest operator input to the active driver only.
+    pub fn set_drive(&mut self, command: DriveCommand) {
+        if let Ok(driver) = self.active_mut() {
+            driver.set_drive(command);
+        }
+    }
+
+    /// Requests e-stop on every running driver; fails only if none of them took it.
+    pub fn estop_all(&mut self) -> Result<(), DriverError> {
+        let mut result = Err(DriverError::NotRunning);
+        for driver in self.drivers.iter_mut().filter(|driver| driver.is_running()) {
+            if driver.request_state(DriverState::EStopped).is_ok() {
+                result = Ok(());
+            }
+        }
+        result
+    }
+
+    /// Probes every enumerated port that has no running driver, in the background;
+    /// the results arrive as one [`Event::Scan`].
+    pub fn scan(&self) -> Result<(), DriverError> {
+        let event_sender = self.event_sender.clone().ok_or(DriverError::NoPortSet)?;
+        let busy: Vec<&str> = self.drivers.iter()
+            .filter(|driver| driver.is_running())
+            .filter_map(|driver| driver.port())
+            .map(|port| port.name())
+            .collect();
+        // By-id links are the same devices again, so only their targets are probed.
+        let transports: Vec<_> = PortInfo::list()?
+            .into_iter()
+            .filter(|port| port.link_to.is_none() && !busy.contains(&port.name.as_str()))
+            .filter_map(|port| transport_for(&port.name, self.line).ok())
+            .collect();
+        tokio::spawn(async move {
+            let results = join_all(transports.into_iter().map(|transport| async move {
+                let outcome = probe(transport.as_ref(), PROBE_TIMEOUT).await;
+                ProbeResult { port: transport.name().to_string(), outcome }
+            })).await;
+            let _ = event_sender.send(Event::Scan(results));
+        });
+        Ok(())
+    }
+
+    /// Summary of every driver for the control panel, active one marked with `*`.
+    pub fn describe(&self) -> String {
+        if self.drivers.is_empty() {
+            return format!("No drivers ({})", self.line);
+        }
+        let drivers: Vec<String> = self.drivers.iter().enumerate().map(|(index, driver)| {
+            let marker = if self.active == Some(index) { "*" } else { "" };
+            let running = if driver.is_running() { "running" } else { "stopped" };
+            format!("{}{} ({})", marker, driver.name(), running)
+        }).collect();
+        let active = match self.active {
+            Some(index) => self.drivers[index].describe_port(),
+            None => String::new(),
+        };
+        format!("Drivers: {}\n{}", drivers.join(", "), active)
+    }
+}
@@ -1,23 +1,32 @@
-use std::{fmt, io, time::Duration};
-
-use crate::{event::Event, pages::{Config, ConfigFnOptions, ControlResult, Window}};
-use serialport::{available_ports, DataBits, Error as SerialPortError, FlowControl, Parity, SerialPort, SerialPortBuilder, StopBits};
-use tokio::sync::mpsc::{self, error::TryRecvError};
-use strum_macros::Display;
-
-#[derive(Debug, Copy, Clone, Display)]
-pub enum DriverState{
-    Active,
-    Connected,
-    Disabled,
-    Enabled,
-}
+use std::{fmt, io, time::{Duration, SystemTime}};
+
+use crate::{event::Event, pages::{Config, ConfigFnOptions, ControlResult, Window}, protocol::{DriveCommand, FrameDecoder, Message, Packet, ProtocolError, RobotIdentity}};
+use serialport::Error as SerialPortError;
+use tokio::{sync::mpsc, time};
+
+use super::{clock_sync::{ground_now_us, ClockSync}, command::{CommandPhase, CommandPolicy, CommandStatus, PendingCommand}, firmware::Upload, link_stats::{LinkMonitor, LinkStats}, params::PendingParam, state_machine::{StateMachine, Transition}, transport::open_link, transport_for, ClockEstimate, DriverState, FirmwareImage, Link, LineSetting, LineSettings, LogEntry, ParamEvent, ParamRequest, PortInfo, RawChunk, RawDirection, TelemetrySample, Transport, UploadProgress};
+
+const HEARTBEAT_PERIOD: Duration = Duration::from_millis(100);
+const DEFAULT_REPORT_PERIOD: Duration = Duration::from_secs(1);
+const STATS_PERIOD: Duration = Duration::from_millis(500);
+const DEFAULT_DRIVE_RATE_HZ: u32 = 50;
+const PRESENCE_CHECK_PERIOD: Duration = Duration::from_secs(1);
+const CLOCK_SYNC_PERIOD: Duration = Duration::from_millis(500);
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_millis(125);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone)]
 pub enum DriverError {
     InvalidPort,
     NoPortSet,
+    NotRunning,
+    UnknownDriver(String),
+    InvalidAddress(String),
     FailedLoadingPorts(SerialPortError),   
+    /// The port or endpoint was there to pick but could not be opened.
+    OpenFailed { port: String, error: SerialPortError },
+    /// Writing to an open link failed.
+    LinkFailed(SerialPortError),
 }
 
 impl fmt::Display for DriverError {
@@ -25,7 +34,12 @@ impl fmt::Display for DriverError {
         match self {
             DriverError::InvalidPort => write!(f, "Invalid port"),
             DriverError::NoPortSet => write!(f, "No port setup"),
+            DriverError::NotRunning => write!(f, "Driver is not running"),
+            DriverError::UnknownDriver(port) => write!(f, "No driver on {}", port),
+            DriverError::InvalidAddress(address) => write!(f, "Invalid address {}: expected host:port", address),
             DriverError::FailedLoadingPorts(e) => write!(f, "Ports couldn't be read: {}", e),
+            DriverError::OpenFailed { port, error } => write!(f, "Couldn't open {}: {}", port, error),
+            DriverError::LinkFailed(e) => write!(f, "Link to robot failed: {}", e),
         }
     }
 }
@@ -36,46 +50,25 @@ impl From<SerialPortError> for DriverError {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DriverPort {
-    port: String,
-    baud: u32,
-    data_bits: DataBits,
-    flow_control: FlowControl,
-    parity: Parity,
-    stop_bits: StopBits
+/// Health of the link as seen by the [`Driver`] supervisor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    Opening,
+    Open,
+    Lost(String),
+    Retrying { attempt: u32, delay: Duration },
 }
 
-impl DriverPort {
-    pub fn new(port: String) -> Result<Self, DriverError>  {
-        let ports = available_ports()?;
-        if let None = ports.iter().find(|info| *info.port_name == port){
-            return Err(DriverError::InvalidPort);
-        }
-        Ok(
-            Self { 
-                port, 
-                baud: 115200,
-                data_bits: DataBits::Eight,
-                flow_control: FlowControl::None, 
-                parity: Parity::None, 
-                stop_bits: StopBits::One,
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkStatus::Opening => write!(f, "Opening port"),
+            LinkStatus::Open => write!(f, "Port open"),
+            LinkStatus::Lost(reason) => write!(f, "Link lost: {}", reason),
+            LinkStatus::Retrying { attempt, delay } => {
+                write!(f, "Retrying (attempt {}) in {} ms", attempt, delay.as_millis())
             }
-        )
-    }
-    
-    pub fn with_baud(mut self, baud: u32) -> Self {
-        self.baud = baud;
-        self
-    }
-
-    pub fn get_ports() -> Result<Vec<String>, DriverError> {
-        let ports = available_ports()?;
-        Ok(ports.iter().map( |port| port.port_name.to_string()).collect())
-    }
-
-    pub fn to_serial_port(&self) -> SerialPortBuilder {
-        serialport::new(&self.port, self.baud)
+        }
     }
 }
 
@@ -83,90 +76,605 @@ impl DriverPort {
 pub enum DriverEvent{
     StateChangeError(String),
     StateChange(DriverState),
+    /// Clears a latched `Faulted` or `EStopped` state.
+    Reset,
+    /// Latest operator input, streamed to the robot while enabled.
+    Drive(DriveCommand),
+    SetDriveRate(u32),
     SetPort(String),
+    SetLine(LineSetting),
+    Reconfigure(Box<dyn Transport>),
+    /// Bytes typed into the serial console, written to the port unframed.
+    SendRaw(Vec<u8>),
+    /// Every chunk read from or written raw to the port, for the serial console.
+    Raw(RawChunk),
+    /// Console bytes that could not be written, and why.
+    RawFailed(String),
+    /// Flashes the image through the robot's bootloader.
+    Upload(FirmwareImage),
+    UploadProgress(UploadProgress),
+    ParamRequest(ParamRequest),
+    Param(ParamEvent),
     StateReport(DriverState),
-    Error(serialport::Error)
+    Transition(Transition),
+    Command(CommandStatus),
+    Link(LinkStatus),
+    LinkStats(LinkStats),
+    Identity(RobotIdentity),
+    /// Latest robot-to-ground clock mapping, after every sync exchange.
+    Clock(ClockEstimate),
+    Telemetry(TelemetrySample),
+    Log(LogEntry),
+    ProtocolError(ProtocolError),
+    Error(DriverError)
+}
+
+/// Why a connected session with the port ended.
+enum SessionEnd {
+    Shutdown,
+    Lost(String),
+    Reconfigured,
 }
 
+/// Async actor that owns the link to the robot.
 #[derive(Debug)]
 pub struct Driver{
-    state: DriverState,
-    port: SerialPortBuilder,
+    name: String,
+    machine: StateMachine,
+    port: Box<dyn Transport>,
     receiver:mpsc::UnboundedReceiver<DriverEvent>,
-    sender: mpsc::UnboundedSender<Event>
+    sender: mpsc::UnboundedSender<Event>,
+    tx_seq: u16,
+    report_period: Duration,
+    command_policy: CommandPolicy,
+    pending: Vec<PendingCommand>,
+    monitor: LinkMonitor,
+    drive: DriveCommand,
+    drive_period: Duration,
+    upload: Option<Upload>,
+    params: Vec<PendingParam>,
+    clock: ClockSync,
 }
 
 impl Driver {
-    pub fn new(receiver:mpsc::UnboundedReceiver<DriverEvent>, sender: mpsc::UnboundedSender<Event>, port: SerialPortBuilder) -> Self {
+    pub fn new(name: String, receiver:mpsc::UnboundedReceiver<DriverEvent>, sender: mpsc::UnboundedSender<Event>, port: Box<dyn Transport>) -> Self {
         Self { 
-            state: DriverState::Active, 
+            name,
+            machine: StateMachine::new(),
             port,
             receiver, 
-            sender}
-    }
-
-    pub fn run(&mut self){
-        let mut requested_state: Option<DriverState> = None;
-        // let mut port = self.port.open();
-        // if let Err(e) = port {
-        //     self.sender.send(Event::Driver(DriverEvent::Error(e))).unwrap();
-        //     return;
-        // } 
-        // if let Err(e) = port.unwrap().set_timeout(Duration::new(0, 1000000)){
-        //     self.sender.send(Event::Driver(DriverEvent::Error(e))).unwrap();
-        //     return;
-        // }
-        let mut count: usize = 0;
+            sender,
+            tx_seq: 0,
+            report_period: DEFAULT_REPORT_PERIOD,
+            command_policy: CommandPolicy::default(),
+            pending: Vec::new(),
+            monitor: LinkMonitor::new(),
+            drive: DriveCommand::neutral(),
+            drive_period: drive_period(DEFAULT_DRIVE_RATE_HZ),
+            upload: None,
+            params: Vec::new(),
+            clock: ClockSync::new(),
+        }
+    }
+
+    /// Sets how often the current state is re-sent to the UI when nothing changes.
+    pub fn with_report_period(mut self, report_period: Duration) -> Self {
+        self.report_period = report_period;
+        self
+    }
+
+    pub fn with_command_policy(mut self, command_policy: CommandPolicy) -> Self {
+        self.command_policy = command_policy;
+        self
+    }
+
+    pub fn with_drive_rate(mut self, rate_hz: u32) -> Self {
+        self.drive_period = drive_period(rate_hz);
+        self
+    }
+
+    /// Opens the port and keeps it open, reconnecting with backoff whenever it is lost.
+    pub async fn run(&mut self){
+        let mut attempt: u32 = 0;
         loop {
-            let sleep_time = Duration::from_millis(1);
-            match self.receiver.try_recv() {
-                Ok(event) => {
-                    match event {
-                        DriverEvent::StateChange(state) => requested_state = Some(state),
-                        _ => (),
+            self.report(DriverEvent::Link(LinkStatus::Opening));
+            match open_link(self.port.as_ref()).await {
+                Ok(mut link) => {
+                    attempt = 0;
+                    self.report(DriverEvent::Link(LinkStatus::Open));
+                    let end = self.run_session(&mut link).await;
+                    // The port must be let go of before it is reopened.
+                    link.close().await;
+                    self.fail_pending("link to robot lost");
+                    self.fail_upload("link to robot lost");
+                    self.fail_params("link to robot lost");
+                    self.link_down();
+                    match end {
+                        SessionEnd::Shutdown => return,
+                        SessionEnd::Reconfigured => continue,
+                        SessionEnd::Lost(reason) => self.report(DriverEvent::Link(LinkStatus::Lost(reason))),
                     }
                 },
-                Err(err) => {
-                    if let TryRecvError::Disconnected = err {
-                        panic!("disconnected channel");
-                    }
+                Err(e) => self.report(DriverEvent::Error(DriverError::OpenFailed { port: self.port.name().to_string(), error: e.into() })),
+            }
+            attempt = attempt.saturating_add(1);
+            let delay = RECONNECT_BACKOFF_MIN
+                .saturating_mul(1 << attempt.min(8))
+                .min(RECONNECT_BACKOFF_MAX);
+            self.report(DriverEvent::Link(LinkStatus::Retrying { attempt, delay }));
+            if !self.wait_for_port(delay).await {
+                return;
+            }
+        }
+    }
+
+    /// Waits for `delay`, then until the port is listed again. Returns false on shutdown.
+    async fn wait_for_port(&mut self, delay: Duration) -> bool {
+        loop {
+            let sleep = time::sleep(delay);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    command = self.receiver.recv() => match command {
+                        Some(DriverEvent::StateChange(state)) => self.request_offline(state),
+                        Some(DriverEvent::Reset) => self.reset(None),
+                        Some(DriverEvent::Reconfigure(port)) => self.port = port,
+                        Some(DriverEvent::Drive(command)) => self.drive = command,
+                        Some(DriverEvent::SetDriveRate(rate_hz)) => self.drive_period = drive_period(rate_hz),
+                        Some(DriverEvent::Upload(image)) => self.refuse_upload(image, "no link to robot"),
+                        Some(DriverEvent::ParamRequest(request)) => self.report(DriverEvent::Param(
+                            ParamEvent::Failed(format!("Cannot {}: no link to robot", request)))),
+                        Some(DriverEvent::SendRaw(bytes)) => self.report(DriverEvent::RawFailed(
+                            format!("Cannot send {} byte(s): port not open", bytes.len()))),
+                        Some(_) => (),
+                        None => return false,
+                    },
                 }
             }
-            //todo remove when done debugging control panel.
-            if requested_state.is_some() {
-                self.state = requested_state.take().unwrap();
+            if self.port.is_present() {
+                return true;
             }
-            match &self.state {
-                DriverState::Active => {
-                    count += 1;
-                    if count % 1000 == 0{
-                        self.state = DriverState::Connected;
+        }
+    }
+
+    async fn run_session(&mut self, link: &mut Link) -> SessionEnd {
+        let mut decoder = FrameDecoder::new();
+        self.monitor = LinkMonitor::new();
+        let mut heartbeat = periodic(HEARTBEAT_PERIOD);
+        let mut stats = periodic(STATS_PERIOD);
+        let mut presence = periodic(PRESENCE_CHECK_PERIOD);
+        let mut report = periodic(self.report_period);
+        let mut drive = periodic(self.drive_period);
+        let mut clock_sync = time::interval(CLOCK_SYNC_PERIOD);
+        clock_sync.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        // A reconnect may well be a rebooted robot with a fresh clock.
+        self.clock = ClockSync::new();
+        if let Err(e) = self.send(link, Message::Hello) {
+            return SessionEnd::Lost(e.to_string());
+        }
+        loop {
+            let next_deadline = self.pending.iter().map(|command| command.deadline)
+                .chain(self.params.iter().map(|param| param.deadline))
+                .chain(self.upload.as_ref().map(|upload| upload.deadline))
+                .min();
+            tokio::select! {
+                command = self.receiver.recv() => match command {
+                    Some(DriverEvent::StateChange(state)) => {
+                        if let Err(e) = self.request(link, state) {
+                            return SessionEnd::Lost(e.to_string());
+                        }
+                    },
+                    Some(DriverEvent::Reset) => self.reset(Some(link)),
+                    Some(DriverEvent::Drive(command)) => self.drive = command,
+                    Some(DriverEvent::SetDriveRate(rate_hz)) => {
+                        self.drive_period = drive_period(rate_hz);
+                        drive = periodic(self.drive_period);
+                    },
+                    Some(DriverEvent::Reconfigure(port)) => {
+                        self.port = port;
+                        return SessionEnd::Reconfigured;
+                    },
+                    Some(DriverEvent::ParamRequest(request)) => {
+                        if let Err(e) = self.send_param(link, request) {
+                            return SessionEnd::Lost(e.to_string());
+                        }
+                    },
+                    Some(DriverEvent::Upload(image)) => {
+                        if let Err(e) = self.start_upload(link, image) {
+                            return SessionEnd::Lost(e.to_string());
+                        }
+                    },
+                    Some(DriverEvent::SendRaw(bytes)) => {
+                        self.monitor.record_tx_bytes(bytes.len());
+                        if let Err(e) = link.send(bytes.clone()) {
+                            return SessionEnd::Lost(e.to_string());
+                        }
+                        self.report(DriverEvent::Raw(RawChunk::new(RawDirection::Tx, bytes)));
+                    },
+                    Some(_) => (),
+                    None => return SessionEnd::Shutdown,
+                },
+                received = link.recv() => {
+                    let bytes = match received {
+                        Ok(bytes) => bytes,
+                        Err(e) => return SessionEnd::Lost(e.to_string()),
+                    };
+                    self.monitor.record_rx_bytes(bytes.len());
+                    self.report(DriverEvent::Raw(RawChunk::new(RawDirection::Rx, bytes.clone())));
+                    for result in decoder.decode(&bytes) {
+                        if let Err(e) = self.handle_packet(link, result) {
+                            return SessionEnd::Lost(e.to_string());
+                        }
                     }
                 },
-                DriverState::Connected => {
-                    count += 1;
-                    if count % 1000 == 0{
-                        self.state = DriverState::Disabled;
+                _ = heartbeat.tick() => {
+                    let stamp = self.monitor.heartbeat_stamp();
+                    if let Err(e) = self.send(link, Message::Heartbeat(stamp)) {
+                        return SessionEnd::Lost(e.to_string());
                     }
                 },
-                DriverState::Disabled => {
-                    count += 1;
-                    if count % 1000 == 0{
-                        self.state = DriverState::Enabled;
+                // Some USB adapters vanish without the read ever failing.
+                _ = presence.tick() => {
+                    if !self.port.is_present() {
+                        return SessionEnd::Lost("port no longer available".to_string());
                     }
                 },
-                DriverState::Enabled => {
-                    count += 1;
-                    if count % 1000 == 0{
-                        self.state = DriverState::Active;
+                _ = time::sleep_until(next_deadline.unwrap_or_else(time::Instant::now)), if next_deadline.is_some() => {
+                    if let Err(e) = self.check_timeouts(link) {
+                        return SessionEnd::Lost(e.to_string());
                     }
+                },
+                _ = drive.tick(), if self.upload.is_none() => {
+                    // Only an enabled robot gets live input; otherwise keep it fed with neutral.
+                    let command = if self.machine.state() == DriverState::Enabled {
+                        self.drive
+                    } else {
+                        DriveCommand::neutral()
+                    };
+                    if let Err(e) = self.send(link, Message::Drive(command)) {
+                        return SessionEnd::Lost(e.to_string());
+                    }
+                },
+                _ = clock_sync.tick() => {
+                    if let Err(e) = self.send(link, Message::TimeRequest { ground_us: ground_now_us() }) {
+                        return SessionEnd::Lost(e.to_string());
+                    }
+                },
+                _ = stats.tick() => {
+                    let snapshot = self.monitor.snapshot();
+                    self.report(DriverEvent::LinkStats(snapshot));
+                },
+                _ = report.tick() => self.report(DriverEvent::StateReport(self.machine.state())),
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, link: &Link, result: Result<Packet, ProtocolError>) -> io::Result<()> {
+        let packet = match result {
+            Ok(packet) => packet,
+            Err(e) => {
+                self.monitor.record_corrupt();
+                self.report(DriverEvent::ProtocolError(e));
+                return Ok(());
+            }
+        };
+        self.monitor.record_frame(packet.seq);
+        if self.machine.state() == DriverState::Active {
+            self.set_state(DriverState::Connected, "robot answered on the link");
+        }
+        match packet.message {
+            Message::Heartbeat(stamp) => self.send(link, Message::HeartbeatAck(stamp))?,
+            Message::HeartbeatAck(stamp) => self.monitor.record_heartbeat_ack(stamp),
+            Message::StateAck { ack_seq, state } => {
+                // Acks for commands no longer pending (duplicates, or superseded by a
+                // reset) must not move the state machine.
+                if let Some(command) = self.take_pending(ack_seq) {
+                    self.report(DriverEvent::Command(command.status(&self.command_policy, CommandPhase::Acked)));
+                    self.robot_state(state);
+                }
+            },
+            Message::Nack { ack_seq, code } if self.upload.as_ref().is_some_and(|upload| upload.seq == ack_seq) => {
+                let reason = self.upload.as_ref().map(|upload| upload.rejection(code)).unwrap_or_default();
+                self.fail_upload(&reason);
+            },
+            Message::Nack { ack_seq, code } => {
+                if let Some(command) = self.take_pending(ack_seq) {
+                    self.fail_command(command, format!("robot rejected command (code {})", code));
+                } else if let Some(param) = self.take_param(ack_seq) {
+                    self.report(DriverEvent::Param(ParamEvent::Failed(
+                        format!("Robot refused to {} (code {})", param.request, code))));
+                }
+            },
+            Message::ParamInfo { count, info } => {
+                self.params.retain(|param| param.request != ParamRequest::List);
+                self.report(DriverEvent::Param(ParamEvent::Info { count, info }));
+            },
+            Message::ParamValue { ack_seq, index, value } => {
+                // Unsolicited values are still the robot's truth, so they are always reported.
+                self.take_param(ack_seq);
+                self.report(DriverEvent::Param(ParamEvent::Value { index, value }));
+            },
+            Message::ParamSaved { ack_seq } => {
+                if self.take_param(ack_seq).is_some() {
+                    self.report(DriverEvent::Param(ParamEvent::Saved));
+                }
+            },
+            Message::BootReady { .. } | Message::FlashAck { .. } | Message::FlashVerified { .. } => {
+                self.upload_reply(link, &packet.message)?;
+            },
+            Message::TimeReply { ground_us, robot_rx_us, robot_tx_us } => {
+                if let Some(estimate) = self.clock.add_exchange(ground_us, robot_rx_us, robot_tx_us, ground_now_us()) {
+                    self.report(DriverEvent::Clock(estimate));
+                }
+            },
+            Message::Telemetry { robot_us, telemetry } => {
+                let ground_time = self.clock.estimate().map(|estimate| estimate.to_ground(robot_us));
+                self.report(DriverEvent::Telemetry(TelemetrySample { robot_us, ground_time, telemetry }));
+            },
+            Message::Log { robot_us, level, tag, text } => {
+                let at = self.clock.estimate().map_or_else(SystemTime::now, |estimate| estimate.to_ground(robot_us));
+                self.report(DriverEvent::Log(LogEntry { robot_us, at, level, tag, text }));
+            },
+            Message::Identity(identity) => self.report(DriverEvent::Identity(identity)),
+            Message::Hello | Message::TimeRequest { .. } => (),
+            Message::StateCommand(_) | Message::Drive(_) => (),
+            Message::BootEnter { .. } | Message::FlashWrite { .. } | Message::FlashVerify { .. } | Message::Reboot => (),
+            Message::ParamList | Message::ParamGet { .. } | Message::ParamSet { .. } | Message::ParamSave => (),
+        }
+        Ok(())
+    }
+
+    /// Validates an operator request and forwards it to the robot.
+    fn request(&mut self, link: &Link, state: DriverState) -> io::Result<()> {
+        if let Err(reason) = self.machine.check_request(state) {
+            self.report(DriverEvent::StateChangeError(format!("Change to {} refused: {}", state, reason)));
+            return Ok(());
+        }
+        // E-stop latches on the ground immediately rather than waiting for the ack.
+        if state == DriverState::EStopped {
+            self.set_state(state, "e-stop requested by operator");
+        }
+        self.send_command(link, state)
+    }
+
+    fn request_offline(&mut self, state: DriverState) {
+        match self.machine.check_request(state) {
+            Ok(()) if state == DriverState::EStopped => self.set_state(state, "e-stop requested by operator"),
+            Ok(()) => self.report(DriverEvent::StateChangeError(
+                format!("Cannot change to {}: no link to robot", state))),
+            Err(reason) => self.report(DriverEvent::StateChangeError(
+                format!("Change to {} refused: {}", state, reason))),
+        }
+    }
+
+    /// Clears a latch locally and asks the robot to come back up disabled.
+    fn reset(&mut self, link: Option<&Link>) {
+        if let Err(reason) = self.machine.check_reset() {
+            self.report(DriverEvent::StateChangeError(format!("Reset refused: {}", reason)));
+            return;
+        }
+        self.fail_pending("superseded by reset");
+        match link {
+            Some(link) => {
+                self.set_state(DriverState::Connected, "latch reset by operator");
+                if let Err(e) = self.send_command(link, DriverState::Disabled) {
+                    self.report(DriverEvent::Error(DriverError::LinkFailed(e.into())));
                 }
+            },
+            None => self.set_state(DriverState::Active, "latch reset by operator"),
+        }
+    }
+
+    /// Applies a state reported by the robot, keeping any ground-side latch.
+    fn robot_state(&mut self, state: DriverState) {
+        let current = self.machine.state();
+        if current.is_latched() && !state.is_latched() {
+            return;
+        }
+        self.set_state(state, "acknowledged by robot");
+    }
+
+    fn link_down(&mut self) {
+        match self.machine.state() {
+            DriverState::Enabled => self.set_state(DriverState::Faulted, "link lost while enabled"),
+            state if state.is_latched() => (),
+            _ => self.set_state(DriverState::Active, "link lost"),
+        }
+    }
+
+    /// Sends a state change and tracks it until the robot acknowledges it.
+    fn send_command(&mut self, link: &Link, state: DriverState) -> io::Result<()> {
+        let packet = Packet::new(self.next_seq(), Message::StateCommand(state));
+        self.transmit(link, &packet)?;
+        let command = PendingCommand::new(packet, state, &self.command_policy);
+        self.report(DriverEvent::Command(command.status(&self.command_policy, CommandPhase::Pending)));
+        self.pending.push(command);
+        Ok(())
+    }
+
+    /// Resends commands whose ack deadline passed, failing those out of retries.
+    fn check_timeouts(&mut self, link: &Link) -> io::Result<()> {
+        let now = time::Instant::now();
+        let (expired, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|command| command.deadline <= now);
+        self.pending = pending;
+        for mut command in expired {
+            if command.attempt > self.command_policy.retries {
+                let reason = format!("no acknowledgement after {} attempts", command.attempt);
+                self.fail_command(command, reason);
+                continue;
+            }
+            // Retries reuse the original sequence number so the robot can drop duplicates.
+            self.transmit(link, &command.packet)?;
+            command.attempt += 1;
+            command.deadline = now + self.command_policy.timeout;
+            self.report(DriverEvent::Command(command.status(&self.command_policy, CommandPhase::Pending)));
+            self.pending.push(command);
+        }
+        let (expired, params): (Vec<_>, Vec<_>) = std::mem::take(&mut self.params)
+            .into_iter()
+            .partition(|param| param.deadline <= now);
+        self.params = params;
+        for mut param in expired {
+            if param.attempt > self.command_policy.retries {
+                self.report(DriverEvent::Param(ParamEvent::Failed(
+                    format!("No reply to {} after {} attempts", param.request, param.attempt))));
+                continue;
+            }
+            self.transmit(link, &param.packet)?;
+            param.attempt += 1;
+            param.deadline = now + self.command_policy.timeout;
+            self.params.push(param);
+        }
+        if let Some(mut upload) = self.upload.take_if(|upload| upload.deadline <= now) {
+            if upload.attempt > self.command_policy.retries {
+                upload.fail(format!("no reply from bootloader after {} attempts", upload.attempt));
+                self.report(DriverEvent::UploadProgress(upload.progress()));
+                return Ok(());
             }
-            self.sender.send(Event::Driver(DriverEvent::StateReport(self.state))).unwrap();
-            std::thread::sleep(sleep_time);
+            if let Some(message) = upload.request() {
+                self.transmit(link, &Packet::new(upload.seq, message))?;
+            }
+            upload.resent(self.command_policy.timeout);
+            self.upload = Some(upload);
         }
-        
+        Ok(())
+    }
+
+    /// Sends a parameter request and tracks it until the robot answers.
+    fn send_param(&mut self, link: &Link, request: ParamRequest) -> io::Result<()> {
+        let packet = Packet::new(self.next_seq(), request.message());
+        self.transmit(link, &packet)?;
+        self.params.push(PendingParam::new(packet, request, &self.command_policy));
+        Ok(())
+    }
+
+    fn take_param(&mut self, seq: u16) -> Option<PendingParam> {
+        let index = self.params.iter().position(|param| param.packet.seq == seq)?;
+        Some(self.params.remove(index))
     }
+
+    fn fail_params(&mut self, reason: &str) {
+        for param in std::mem::take(&mut self.params) {
+            self.report(DriverEvent::Param(ParamEvent::Failed(format!("Cannot {}: {}", param.request, reason))));
+        }
+    }
+
+    fn start_upload(&mut self, link: &Link, image: FirmwareImage) -> io::Result<()> {
+        if self.upload.is_some() {
+            self.refuse_upload(image, "another upload is in progress");
+            return Ok(());
+        }
+        if self.machine.state() == DriverState::Enabled {
+            self.refuse_upload(image, "disable the robot first");
+            return Ok(());
+        }
+        self.upload_step(link, Upload::new(image))
+    }
+
+    fn refuse_upload(&self, image: FirmwareImage, reason: &str) {
+        let mut upload = Upload::new(image);
+        upload.fail(reason.to_string());
+        self.report(DriverEvent::UploadProgress(upload.progress()));
+    }
+
+    fn upload_reply(&mut self, link: &Link, reply: &Message) -> io::Result<()> {
+        let Some(mut upload) = self.upload.take() else {
+            return Ok(());
+        };
+        if upload.accept(reply) {
+            return self.upload_step(link, upload);
+        }
+        // A late duplicate of a reply already handled.
+        self.upload = Some(upload);
+        Ok(())
+    }
+
+    /// Sends the upload's next request, or reboots the robot once the image verified.
+    fn upload_step(&mut self, link: &Link, mut upload: Upload) -> io::Result<()> {
+        match upload.request() {
+            Some(message) => {
+                let packet = Packet::new(self.next_seq(), message);
+                self.transmit(link, &packet)?;
+                upload.sent(packet.seq, self.command_policy.timeout);
+                self.report(DriverEvent::UploadProgress(upload.progress()));
+                self.upload = Some(upload);
+            },
+            None => {
+                if upload.is_done() {
+                    self.send(link, Message::Reboot)?;
+                }
+                self.report(DriverEvent::UploadProgress(upload.progress()));
+            },
+        }
+        Ok(())
+    }
+
+    fn fail_upload(&mut self, reason: &str) {
+        if let Some(mut upload) = self.upload.take() {
+            upload.fail(reason.to_string());
+            self.report(DriverEvent::UploadProgress(upload.progress()));
+        }
+    }
+
+    fn take_pending(&mut self, seq: u16) -> Option<PendingCommand> {
+        let index = self.pending.iter().position(|command| command.packet.seq == seq)?;
+        Some(self.pending.remove(index))
+    }
+
+    fn fail_command(&mut self, command: PendingCommand, reason: String) {
+        self.report(DriverEvent::Command(command.status(&self.command_policy, CommandPhase::Failed(reason.clone()))));
+        self.report(DriverEvent::StateChangeError(format!("Change to {} failed: {}", command.state, reason)));
+    }
+
+    fn fail_pending(&mut self, reason: &str) {
+        for command in std::mem::take(&mut self.pending) {
+            self.fail_command(command, reason.to_string());
+        }
+    }
+
+    /// Updates the state, logging the transition when it actually changed.
+    fn set_state(&mut self, state: DriverState, reason: &str) {
+        if let Some(transition) = self.machine.transition(state, reason) {
+            self.report(DriverEvent::Transition(transition));
+            self.report(DriverEvent::StateReport(state));
+        }
+    }
+
+    fn send(&mut self, link: &Link, message: Message) -> io::Result<()> {
+        let packet = Packet::new(self.next_seq(), message);
+        self.transmit(link, &packet)
+    }
+
+    fn transmit(&mut self, link: &Link, packet: &Packet) -> io::Result<()> {
+        let frame = packet.encode();
+        self.monitor.record_tx_bytes(frame.len());
+        link.send(frame)
+    }
+
+    fn next_seq(&mut self) -> u16 {
+        let seq = self.tx_seq;
+        self.tx_seq = self.tx_seq.wrapping_add(1);
+        seq
+    }
+
+    fn report(&self, event: DriverEvent) {
+        // The UI owning the receiver may already be gone on shutdown.
+        let _ = self.sender.send(Event::Driver(self.name.clone(), event));
+    }
+}
+
+fn drive_period(rate_hz: u32) -> Duration {
+    Duration::from_secs(1) / rate_hz.max(1)
+}
+
+/// An interval whose first tick is one period from now and that never bursts to catch up.
+fn periodic(period: Duration) -> time::Interval {
+    let mut interval = time::interval_at(time::Instant::now() + period, period);
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    interval
 }
 
 // #[derive(Debug)]
@@ -177,36 +685,140 @@ impl Driver {
 
 #[derive(Debug)]
 pub struct DriverTask {
+    name: String,
     event_sender: Option<mpsc::UnboundedSender<Event>>,
     //driver_event_sender: mpsc::UnboundedSender<DriverEvent>,
     to_driver_sender: mpsc::UnboundedSender<DriverEvent>,
     to_driver_receiver: Option<mpsc::UnboundedReceiver<DriverEvent>>,
     //driver: Driver,
     task: Option<tokio::task::JoinHandle<()>>,
-    port: Option<DriverPort>,
+    port: Option<Box<dyn Transport>>,
+    line: LineSettings,
+    report_period: Duration,
+    command_policy: CommandPolicy,
+    drive_rate_hz: u32,
 }
 
 impl DriverTask{
-    pub fn new() -> Self {
+    pub fn new(name: String) -> Self {
         let (to_driver_sender, to_driver_reciever)
             = mpsc::unbounded_channel();
         Self{
+            name,
             event_sender : None,
             to_driver_sender,
             to_driver_receiver: Some(to_driver_reciever),
             task: None,
             port: None,
+            line: LineSettings::default(),
+            report_period: DEFAULT_REPORT_PERIOD,
+            command_policy: CommandPolicy::default(),
+            drive_rate_hz: DEFAULT_DRIVE_RATE_HZ,
         }
     }
 
+    /// Name the driver's events are tagged with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn port(&self) -> Option<&dyn Transport> {
+        self.port.as_deref()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    /// Sets the periodic state report rate used by drivers started after this call.
+    pub fn set_report_period(&mut self, report_period: Duration) {
+        self.report_period = report_period;
+    }
+
+    /// Sets the ack deadline and retry count used by drivers started after this call.
+    pub fn set_command_policy(&mut self, command_policy: CommandPolicy) {
+        self.command_policy = command_policy;
+    }
+
+    /// Points the driver at a serial device, `tcp://host:port` or `udp://host:port`.
     pub fn set_port(&mut self, name: String) -> Result<(), DriverError> {
-        let port = DriverPort::new(name)?;
-        self.port = Some(port);
+        self.set_transport(transport_for(&name, self.line)?);
         Ok(())
     }
 
-    pub fn request_state(&mut self, state: DriverState) {
-        self.to_driver_sender.send(DriverEvent::StateChange(state)).unwrap()
+    pub fn set_transport(&mut self, transport: Box<dyn Transport>) {
+        self.port = Some(transport);
+        self.reconfigure_driver();
+    }
+
+    pub fn set_line_settings(&mut self, line: LineSettings) {
+        self.line = line;
+        if let Some(port) = self.port.as_mut() {
+            port.set_line(self.line);
+            self.reconfigure_driver();
+        }
+    }
+
+    pub fn line_settings(&self) -> LineSettings {
+        self.line
+    }
+
+    pub fn set_line(&mut self, setting: LineSetting) {
+        let mut line = self.line;
+        line.apply(setting);
+        self.set_line_settings(line);
+    }
+
+    /// Describes the selected port and line settings for the control panel.
+    pub fn describe_port(&self) -> String {
+        match &self.port {
+            Some(port) => format!("Driver {} port: {}", self.name, port),
+            None => format!("Driver {} port: none selected ({})", self.name, self.line),
+        }
+    }
+
+    /// Hands the current port settings to a running driver so it reopens the port.
+    fn reconfigure_driver(&mut self) {
+        if let (Some(_), Some(port)) = (&self.task, &self.port) {
+            let _ = self.to_driver_sender.send(DriverEvent::Reconfigure(port.clone()));
+        }
+    }
+
+    pub fn request_state(&mut self, state: DriverState) -> Result<(), DriverError> {
+        self.send_to_driver(DriverEvent::StateChange(state))
+    }
+
+    pub fn reset(&mut self) -> Result<(), DriverError> {
+        self.send_to_driver(DriverEvent::Reset)
+    }
+
+    /// Hands the latest operator input to the driver; dropped if no driver is running.
+    pub fn set_drive(&mut self, command: DriveCommand) {
+        let _ = self.send_to_driver(DriverEvent::Drive(command));
+    }
+
+    pub fn send_raw(&mut self, bytes: Vec<u8>) -> Result<(), DriverError> {
+        self.send_to_driver(DriverEvent::SendRaw(bytes))
+    }
+
+    pub fn upload_firmware(&mut self, image: FirmwareImage) -> Result<(), DriverError> {
+        self.send_to_driver(DriverEvent::Upload(image))
+    }
+
+    pub fn request_param(&mut self, request: ParamRequest) -> Result<(), DriverError> {
+        self.send_to_driver(DriverEvent::ParamRequest(request))
+    }
+
+    pub fn set_drive_rate(&mut self, rate_hz: u32) {
+        self.drive_rate_hz = rate_hz;
+        let _ = self.send_to_driver(DriverEvent::SetDriveRate(rate_hz));
+    }
+
+    fn send_to_driver(&mut self, event: DriverEvent) -> Result<(), DriverError> {
+        if self.task.is_none() {
+            return Err(DriverError::NotRunning);
+        }
+        self.to_driver_sender.send(event).map_err(|_| DriverError::NotRunning)
     }
 
     pub fn set_sender(&mut self, event_sender: mpsc::UnboundedSender<Event>) {
@@ -214,35 +826,56 @@ impl DriverTask{
     }
 
     pub fn start_driver(&mut self) -> Result<(), DriverError> {
-        if self.port.is_none() || self.event_sender.is_none() || self.port.is_none() {
-            return Err(DriverError::NoPortSet);
-        }
-        if let Some(_) = self.task {
-            //TODO decide what to do if driver already started. 
+        if self.task.is_some() {
+            // A running driver picks up port changes through `reconfigure_driver`.
             return Ok(());
         }
+        if self.port.is_none() || self.event_sender.is_none() {
+            return Err(DriverError::NoPortSet);
+        }
         let to_driver_receiver = self.to_driver_receiver.take().unwrap();
-        let sender = self.event_sender.take().unwrap();
-        let port = self.port.take().unwrap();
-        tokio::spawn(async move {
-            let mut driver = Driver::new(to_driver_receiver, sender, port.to_serial_port());
-            loop {
-                driver.run();
-            }
-        });
+        let sender = self.event_sender.clone().unwrap();
+        let port = self.port.clone().unwrap();
+        let mut driver = Driver::new(self.name.clone(), to_driver_receiver, sender, port)
+            .with_report_period(self.report_period)
+            .with_command_policy(self.command_policy)
+            .with_drive_rate(self.drive_rate_hz);
+        self.task = Some(tokio::spawn(async move {
+            driver.run().await;
+        }));
         Ok(())
     }
 
+    /// Closes the driver's command channel so it shuts down, leaving the task ready to start again.
+    pub fn stop_driver(&mut self) -> Result<(), DriverError> {
+        if self.task.take().is_none() {
+            return Err(DriverError::NotRunning);
+        }
+        let (to_driver_sender, to_driver_receiver) = mpsc::unbounded_channel();
+        self.to_driver_sender = to_driver_sender;
+        self.to_driver_receiver = Some(to_driver_receiver);
+        Ok(())
+    }
+
+    /// Every port labelled with how it is attached, plus the by-id links to USB adapters.
+    /// A failed enumeration is shown in the window instead.
     pub fn list_ports() -> (Window, Option<ControlResult>) {
-        //todo fix with error handling
-        let ports = DriverPort::get_ports().unwrap();
-        (Window::new("Available ports".to_string()).with_configs(ports
+        let window = Window::new("Available ports".to_string());
+        let ports = match PortInfo::list() {
+            Ok(ports) => ports,
+            Err(e) => return (window.with_configs(vec![Config::new(format!("Cannot list ports: {}", e))]), None),
+        };
+        if ports.is_empty() {
+            return (window.with_configs(vec![Config::new("No ports found".to_string())]), None);
+        }
+        (window.with_configs(ports
             .iter()
-            .map(|s| 
-                Config::new(s.clone())
+            .map(|port| 
+                Config::new(port.label())
+                .with_value(port.name.clone())
                 .with_on_select(
                     ConfigFnOptions::ConfigToNone(|config| 
-                            Some(ControlResult::DriverChange(DriverEvent::SetPort(config.get_short_text().to_string())))
+                            Some(ControlResult::DriverChange(DriverEvent::SetPort(config.get_value().to_string())))
                 )
                 )
             ).collect()
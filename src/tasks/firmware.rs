@@ -0,0 +1,269 @@
+use std::{fmt, fs, path::Path, time::Duration};
+
+use tokio::time;
+
+use crate::protocol::{crc32, Message};
+
+/// Largest write the ground station sends, whatever the bootloader allows.
+const MAX_CHUNK: usize = 512;
+/// Guards against HEX files whose records are spread over a huge address range.
+const MAX_IMAGE_LEN: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FirmwareError {
+    Io(String),
+    /// An Intel HEX record that could not be used, with its 1-based line number.
+    BadHex { line: usize, reason: String },
+    Empty,
+    TooLarge(usize),
+}
+
+impl fmt::Display for FirmwareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FirmwareError::Io(e) => write!(f, "Couldn't read image: {}", e),
+            FirmwareError::BadHex { line, reason } => write!(f, "Bad Intel HEX on line {}: {}", line, reason),
+            FirmwareError::Empty => write!(f, "Image is empty"),
+            FirmwareError::TooLarge(len) => write!(f, "Image spans {} bytes, more than any flash", len),
+        }
+    }
+}
+
+/// A flat application image, as the bootloader stores it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirmwareImage {
+    pub name: String,
+    /// Load address of the first byte; raw binaries don't say, and are flashed
+    /// wherever the bootloader puts the application.
+    pub base_address: Option<u32>,
+    pub data: Vec<u8>,
+}
+
+impl FirmwareImage {
+    /// Reads a raw binary, or an Intel HEX file when the extension is `.hex` or `.ihex`.
+    pub fn load(path: &Path) -> Result<Self, FirmwareError> {
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let is_hex = path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("hex") || extension.eq_ignore_ascii_case("ihex"));
+        if is_hex {
+            let text = fs::read_to_string(path).map_err(|e| FirmwareError::Io(e.to_string()))?;
+            Self::parse_hex(name, &text)
+        } else {
+            let data = fs::read(path).map_err(|e| FirmwareError::Io(e.to_string()))?;
+            Self::from_bytes(name, data)
+        }
+    }
+
+    pub fn from_bytes(name: String, data: Vec<u8>) -> Result<Self, FirmwareError> {
+        if data.is_empty() {
+            return Err(FirmwareError::Empty);
+        }
+        if data.len() > MAX_IMAGE_LEN {
+            return Err(FirmwareError::TooLarge(data.len()));
+        }
+        Ok(Self { name, base_address: None, data })
+    }
+
+    /// Flattens Intel HEX data records into one image starting at the lowest address,
+    /// filling gaps with `0xFF` as erased flash reads.
+    pub fn parse_hex(name: String, text: &str) -> Result<Self, FirmwareError> {
+        let mut records: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut upper: u32 = 0;
+        for (index, line) in text.lines().enumerate() {
+            let bad = |reason: &str| FirmwareError::BadHex { line: index + 1, reason: reason.to_string() };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let hex = line.strip_prefix(':').ok_or_else(|| bad("missing ':'"))?;
+            if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+                return Err(bad("odd number of hex digits"));
+            }
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| bad("invalid hex digit"))?;
+            if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+                return Err(bad("length doesn't match byte count"));
+            }
+            if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+                return Err(bad("checksum mismatch"));
+            }
+            let address = u16::from_be_bytes([bytes[1], bytes[2]]) as u32;
+            let data = &bytes[4..bytes.len() - 1];
+            match bytes[3] {
+                0x00 => records.push((upper + address, data.to_vec())),
+                0x01 => break,
+                0x02 if data.len() == 2 => upper = (u16::from_be_bytes([data[0], data[1]]) as u32) << 4,
+                0x04 if data.len() == 2 => upper = (u16::from_be_bytes([data[0], data[1]]) as u32) << 16,
+                // Start address records don't affect the image.
+                0x03 | 0x05 => (),
+                _ => return Err(bad("unsupported record")),
+            }
+        }
+        let base_address = records.iter().map(|(address, _)| *address).min().ok_or(FirmwareError::Empty)?;
+        let end = records.iter().map(|(address, data)| *address as usize + data.len()).max().unwrap_or(0);
+        let len = end - base_address as usize;
+        if len > MAX_IMAGE_LEN {
+            return Err(FirmwareError::TooLarge(len));
+        }
+        let mut image = vec![0xFF; len];
+        for (address, data) in records {
+            let start = (address - base_address) as usize;
+            image[start..start + data.len()].copy_from_slice(&data);
+        }
+        Ok(Self { name, base_address: Some(base_address), data: image })
+    }
+
+    pub fn crc(&self) -> u32 {
+        crc32(&self.data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadStage {
+    EnteringBootloader,
+    Writing,
+    Verifying,
+    Done,
+    Failed(String),
+}
+
+/// Where a firmware upload has got to, reported after every step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub image: String,
+    pub stage: UploadStage,
+    pub sent: usize,
+    pub total: usize,
+}
+
+impl UploadProgress {
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.sent as f64 / self.total as f64
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.stage, UploadStage::Done | UploadStage::Failed(_))
+    }
+}
+
+impl fmt::Display for UploadProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.stage {
+            UploadStage::EnteringBootloader => write!(f, "{}: entering bootloader", self.image),
+            UploadStage::Writing => write!(f, "{}: writing {}/{} bytes", self.image, self.sent, self.total),
+            UploadStage::Verifying => write!(f, "{}: verifying CRC", self.image),
+            UploadStage::Done => write!(f, "{}: verified and rebooted", self.image),
+            UploadStage::Failed(reason) => write!(f, "{}: upload failed: {}", self.image, reason),
+        }
+    }
+}
+
+/// A firmware upload in flight: one request outstanding at a time, each acknowledged
+/// before the next is sent and resent on timeout.
+#[derive(Debug)]
+pub(super) struct Upload {
+    image: FirmwareImage,
+    stage: UploadStage,
+    offset: usize,
+    chunk: usize,
+    /// Sequence number of the outstanding request, reused on resend.
+    pub seq: u16,
+    pub attempt: u32,
+    pub deadline: time::Instant,
+}
+
+impl Upload {
+    pub fn new(image: FirmwareImage) -> Self {
+        Self {
+            image,
+            stage: UploadStage::EnteringBootloader,
+            offset: 0,
+            chunk: MAX_CHUNK,
+            seq: 0,
+            attempt: 0,
+            deadline: time::Instant::now(),
+        }
+    }
+
+    /// The request for the current stage; `None` once the upload has finished.
+    pub fn request(&self) -> Option<Message> {
+        match self.stage {
+            UploadStage::EnteringBootloader => Some(Message::BootEnter { base_address: self.image.base_address }),
+            UploadStage::Writing => {
+                let end = (self.offset + self.chunk).min(self.image.data.len());
+                Some(Message::FlashWrite { offset: self.offset as u32, data: self.image.data[self.offset..end].to_vec() })
+            }
+            UploadStage::Verifying => Some(Message::FlashVerify { length: self.image.data.len() as u32, crc: self.image.crc() }),
+            UploadStage::Done | UploadStage::Failed(_) => None,
+        }
+    }
+
+    /// Records that the current request went out as `seq`.
+    pub fn sent(&mut self, seq: u16, timeout: Duration) {
+        self.seq = seq;
+        self.attempt = 1;
+        self.deadline = time::Instant::now() + timeout;
+    }
+
+    pub fn resent(&mut self, timeout: Duration) {
+        self.attempt += 1;
+        self.deadline = time::Instant::now() + timeout;
+    }
+
+    /// Applies a reply from the bootloader; returns true if it moved the upload on.
+    pub fn accept(&mut self, reply: &Message) -> bool {
+        match (&self.stage, reply) {
+            (UploadStage::EnteringBootloader, Message::BootReady { max_chunk }) => {
+                self.chunk = (*max_chunk as usize).clamp(1, MAX_CHUNK);
+                self.stage = UploadStage::Writing;
+            }
+            (UploadStage::Writing, Message::FlashAck { offset }) if *offset as usize == self.offset => {
+                self.offset = (self.offset + self.chunk).min(self.image.data.len());
+                if self.offset == self.image.data.len() {
+                    self.stage = UploadStage::Verifying;
+                }
+            }
+            (UploadStage::Verifying, Message::FlashVerified { crc }) => {
+                self.stage = if *crc == self.image.crc() {
+                    UploadStage::Done
+                } else {
+                    UploadStage::Failed(format!("CRC mismatch: robot has {:#010x}, image is {:#010x}", crc, self.image.crc()))
+                };
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Why the bootloader refused the outstanding request, as the operator should read it.
+    pub fn rejection(&self, code: u8) -> String {
+        match (&self.stage, self.image.base_address) {
+            (UploadStage::EnteringBootloader, Some(base)) => format!("robot won't flash an image linked at {:#010x} (code {})", base, code),
+            _ => format!("bootloader rejected request (code {})", code),
+        }
+    }
+
+    pub fn fail(&mut self, reason: String) {
+        self.stage = UploadStage::Failed(reason);
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stage == UploadStage::Done
+    }
+
+    pub fn progress(&self) -> UploadProgress {
+        UploadProgress {
+            image: self.image.name.clone(),
+            stage: self.stage.clone(),
+            sent: self.offset,
+            total: self.image.data.len(),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{io::{self, Read, Write}, sync::{atomic::{AtomicBool, Ordering}, mpsc as std_mpsc, Arc}, thread::{self, JoinHandle}};
+
+use tokio::{sync::mpsc, task};
+
+const READ_CHUNK: usize = 256;
+
+/// Non-blocking handle on an open transport.
+///
+/// Serial ports and std sockets only offer blocking I/O, so each link is driven by a
+/// reader and a writer thread and the async side talks to them over channels. The
+/// reader must have a read timeout, and the writer a write timeout, so both notice when
+/// the link is closed or dropped. [`Link::close`] waits for them to let go of the port.
+#[derive(Debug)]
+pub struct Link {
+    incoming: mpsc::UnboundedReceiver<io::Result<Vec<u8>>>,
+    /// Taken on close or drop, which lets the writer thread finish.
+    outgoing: Option<std_mpsc::Sender<Vec<u8>>>,
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl Link {
+    pub fn new(mut reader: impl Read + Send + 'static, mut writer: impl Write + Send + 'static) -> Self {
+        let (incoming_sender, incoming) = mpsc::unbounded_channel();
+        let (outgoing, outgoing_receiver) = std_mpsc::channel::<Vec<u8>>();
+
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let read_sender = incoming_sender.clone();
+        let read_stop = stop.clone();
+        let reader = thread::spawn(move || {
+            let mut buf = [0u8; READ_CHUNK];
+            while !read_stop.load(Ordering::Relaxed) && !read_sender.is_closed() {
+                let result = match reader.read(&mut buf) {
+                    Ok(0) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "port hung up")),
+                    Ok(count) => Ok(buf[..count].to_vec()),
+                    // Sockets report an expired read timeout as `WouldBlock` on Unix.
+                    Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock) => continue,
+                    Err(e) => Err(e),
+                };
+                let failed = result.is_err();
+                if read_sender.send(result).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        let writer = thread::spawn(move || {
+            while let Ok(bytes) = outgoing_receiver.recv() {
+                if let Err(e) = writer.write_all(&bytes).and_then(|_| writer.flush()) {
+                    let _ = incoming_sender.send(Err(e));
+                    return;
+                }
+            }
+        });
+
+        Self { incoming, outgoing: Some(outgoing), stop, threads: vec![reader, writer] }
+    }
+
+    /// Waits for the next chunk of received bytes, or the error that ended the link.
+    pub async fn recv(&mut self) -> io::Result<Vec<u8>> {
+        match self.incoming.recv().await {
+            Some(result) => result,
+            None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "link threads stopped")),
+        }
+    }
+
+    /// Stops both threads and waits, on the blocking pool, for them to let go of the
+    /// port, so it can be reopened straight away. Takes at most a read or write timeout.
+    pub async fn close(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.outgoing = None;
+        let threads = std::mem::take(&mut self.threads);
+        let _ = task::spawn_blocking(move || {
+            for thread in threads {
+                let _ = thread.join();
+            }
+        }).await;
+    }
+
+    /// Queues bytes for the writer thread without waiting for them to go out.
+    pub fn send(&self, bytes: Vec<u8>) -> io::Result<()> {
+        self.outgoing
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "link closed"))?
+            .send(bytes)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "link writer stopped"))
+    }
+}
+
+impl Drop for Link {
+    /// Tells both threads to stop without waiting for them; [`Link::close`] waits.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.outgoing = None;
+    }
+}
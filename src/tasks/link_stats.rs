@@ -0,0 +1,123 @@
+use std::{collections::VecDeque, time::Duration};
+
+use tokio::time::Instant;
+
+/// Number of heartbeat round trips averaged into the reported RTT.
+const RTT_WINDOW: usize = 16;
+
+/// Snapshot of link health published to the UI.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkStats {
+    /// Rolling average heartbeat round-trip time.
+    pub rtt: Option<Duration>,
+    pub frames_received: u64,
+    pub frames_lost: u64,
+    pub frames_out_of_order: u64,
+    pub frames_corrupt: u64,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    /// Time since the last valid frame from the robot.
+    pub since_last_frame: Option<Duration>,
+}
+
+impl LinkStats {
+    /// Fraction of frames the robot sent that never arrived.
+    pub fn loss_ratio(&self) -> f64 {
+        let expected = self.frames_received + self.frames_lost;
+        if expected == 0 {
+            0.0
+        } else {
+            self.frames_lost as f64 / expected as f64
+        }
+    }
+}
+
+/// Accumulates link counters over a session and turns them into [`LinkStats`].
+#[derive(Debug)]
+pub struct LinkMonitor {
+    start: Instant,
+    stats: LinkStats,
+    last_rx_seq: Option<u16>,
+    rtt_samples: VecDeque<Duration>,
+    rx_bytes: u64,
+    tx_bytes: u64,
+    window_start: Instant,
+    last_frame: Option<Instant>,
+}
+
+impl LinkMonitor {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            stats: LinkStats::default(),
+            last_rx_seq: None,
+            rtt_samples: VecDeque::with_capacity(RTT_WINDOW),
+            rx_bytes: 0,
+            tx_bytes: 0,
+            window_start: Instant::now(),
+            last_frame: None,
+        }
+    }
+
+    pub fn record_rx_bytes(&mut self, count: usize) {
+        self.rx_bytes += count as u64;
+    }
+
+    pub fn record_tx_bytes(&mut self, count: usize) {
+        self.tx_bytes += count as u64;
+    }
+
+    pub fn record_corrupt(&mut self) {
+        self.stats.frames_corrupt += 1;
+    }
+
+    /// Tracks the robot's sequence numbers to count gaps and reordering.
+    pub fn record_frame(&mut self, seq: u16) {
+        self.stats.frames_received += 1;
+        self.last_frame = Some(Instant::now());
+        let Some(last) = self.last_rx_seq else {
+            self.last_rx_seq = Some(seq);
+            return;
+        };
+        let gap = seq.wrapping_sub(last.wrapping_add(1));
+        if gap == 0 {
+            self.last_rx_seq = Some(seq);
+        } else if gap < 0x8000 {
+            self.stats.frames_lost += gap as u64;
+            self.last_rx_seq = Some(seq);
+        } else {
+            self.stats.frames_out_of_order += 1;
+        }
+    }
+
+    /// Milliseconds since the session started, carried by outgoing heartbeats.
+    pub fn heartbeat_stamp(&self) -> u32 {
+        self.start.elapsed().as_millis() as u32
+    }
+
+    /// Turns the echoed stamp of a heartbeat ack into a round-trip sample.
+    pub fn record_heartbeat_ack(&mut self, stamp: u32) {
+        let rtt = Duration::from_millis(self.heartbeat_stamp().wrapping_sub(stamp) as u64);
+        if self.rtt_samples.len() == RTT_WINDOW {
+            self.rtt_samples.pop_front();
+        }
+        self.rtt_samples.push_back(rtt);
+        let total: Duration = self.rtt_samples.iter().sum();
+        self.stats.rtt = Some(total / self.rtt_samples.len() as u32);
+    }
+
+    /// Closes the current throughput window and returns the updated snapshot.
+    pub fn snapshot(&mut self) -> LinkStats {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.window_start).as_secs_f64();
+        if elapsed > 0.0 {
+            self.stats.rx_bytes_per_sec = self.rx_bytes as f64 / elapsed;
+            self.stats.tx_bytes_per_sec = self.tx_bytes as f64 / elapsed;
+        }
+        self.rx_bytes = 0;
+        self.tx_bytes = 0;
+        self.window_start = now;
+        self.stats.since_last_frame = self.last_frame.map(|last| now.duration_since(last));
+        self.stats.clone()
+    }
+}
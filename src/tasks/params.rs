@@ -0,0 +1,68 @@
+use std::fmt;
+
+use tokio::time::Instant;
+
+use crate::protocol::{Message, Packet, ParamInfo, ParamValue};
+
+use super::CommandPolicy;
+
+/// Operator requests to the robot's parameter service.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamRequest {
+    List,
+    Get(u16),
+    Set(u16, ParamValue),
+    /// Stores the current values in the robot's flash.
+    Save,
+}
+
+impl ParamRequest {
+    pub(super) fn message(&self) -> Message {
+        match self {
+            ParamRequest::List => Message::ParamList,
+            ParamRequest::Get(index) => Message::ParamGet { index: *index },
+            ParamRequest::Set(index, value) => Message::ParamSet { index: *index, value: *value },
+            ParamRequest::Save => Message::ParamSave,
+        }
+    }
+}
+
+impl fmt::Display for ParamRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamRequest::List => write!(f, "list parameters"),
+            ParamRequest::Get(index) => write!(f, "read parameter #{}", index),
+            ParamRequest::Set(index, value) => write!(f, "set parameter #{} to {}", index, value),
+            ParamRequest::Save => write!(f, "save parameters"),
+        }
+    }
+}
+
+/// Replies from the parameter service, and requests that got none.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamEvent {
+    Info { count: u16, info: ParamInfo },
+    Value { index: u16, value: ParamValue },
+    Saved,
+    Failed(String),
+}
+
+/// A parameter request that has not been answered yet.
+#[derive(Debug)]
+pub struct PendingParam {
+    pub packet: Packet,
+    pub request: ParamRequest,
+    pub attempt: u32,
+    pub deadline: Instant,
+}
+
+impl PendingParam {
+    pub fn new(packet: Packet, request: ParamRequest, policy: &CommandPolicy) -> Self {
+        Self {
+            packet,
+            request,
+            attempt: 1,
+            deadline: Instant::now() + policy.timeout,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::{fmt, time::{SystemTime, UNIX_EPOCH}};
+
+/// Wall-clock time of day, shown as `HH:MM:SS.mmm UTC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOfDay(pub SystemTime);
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let millis = self.0.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+        let secs = millis / 1000 % 86400;
+        write!(f, "{:02}:{:02}:{:02}.{:03} UTC", secs / 3600, secs / 60 % 60, secs % 60, millis % 1000)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawDirection {
+    Rx,
+    Tx,
+}
+
+/// Bytes exactly as they crossed the serial port, for the raw console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawChunk {
+    pub at: SystemTime,
+    pub direction: RawDirection,
+    pub bytes: Vec<u8>,
+}
+
+impl RawChunk {
+    pub fn new(direction: RawDirection, bytes: Vec<u8>) -> Self {
+        Self { at: SystemTime::now(), direction, bytes }
+    }
+}
@@ -0,0 +1,130 @@
+use std::{fmt, time::SystemTime};
+
+use strum_macros::Display;
+
+use super::TimeOfDay;
+
+#[derive(Debug, Copy, Clone, Display, PartialEq, Eq)]
+pub enum DriverState{
+    Active,
+    Connected,
+    Disabled,
+    Enabled,
+    Faulted,
+    EStopped,
+}
+
+impl DriverState {
+    /// Latched states are only left through an explicit reset.
+    pub fn is_latched(self) -> bool {
+        matches!(self, DriverState::Faulted | DriverState::EStopped)
+    }
+}
+
+impl From<DriverState> for u8 {
+    fn from(state: DriverState) -> u8 {
+        match state {
+            DriverState::Active => 0,
+            DriverState::Connected => 1,
+            DriverState::Disabled => 2,
+            DriverState::Enabled => 3,
+            DriverState::Faulted => 4,
+            DriverState::EStopped => 5,
+        }
+    }
+}
+
+impl TryFrom<u8> for DriverState {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DriverState::Active),
+            1 => Ok(DriverState::Connected),
+            2 => Ok(DriverState::Disabled),
+            3 => Ok(DriverState::Enabled),
+            4 => Ok(DriverState::Faulted),
+            5 => Ok(DriverState::EStopped),
+            other => Err(other),
+        }
+    }
+}
+
+/// A state change that happened, kept for the operator's transition log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub at: SystemTime,
+    pub from: DriverState,
+    pub to: DriverState,
+    pub reason: String,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} -> {}: {}", TimeOfDay(self.at), self.from, self.to, self.reason)
+    }
+}
+
+/// Ground-side view of the robot state that only allows legal transitions.
+///
+/// Operator requests go through [`StateMachine::check_request`]; link and robot
+/// events go straight to [`StateMachine::transition`].
+#[derive(Debug)]
+pub struct StateMachine {
+    state: DriverState,
+}
+
+impl Default for StateMachine {
+    fn default() -> Self {
+        Self { state: DriverState::Active }
+    }
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> DriverState {
+        self.state
+    }
+
+    /// Checks whether the operator may ask the robot to move to `to`.
+    pub fn check_request(&self, to: DriverState) -> Result<(), String> {
+        use DriverState::*;
+        match (self.state, to) {
+            (_, EStopped) => Ok(()),
+            (from, _) if from.is_latched() => Err(format!("{} is latched, reset first", from)),
+            (_, Active | Connected | Faulted) => Err(format!("{} cannot be requested", to)),
+            (Active, _) => Err("no link to robot".to_string()),
+            (Connected | Enabled, Disabled) => Ok(()),
+            (Disabled, Enabled) => Ok(()),
+            (Connected, Enabled) => Err("robot must be disabled before enabling".to_string()),
+            (from, to) if from == to => Err(format!("already {}", to)),
+            (from, to) => Err(format!("illegal transition {} -> {}", from, to)),
+        }
+    }
+
+    pub fn check_reset(&self) -> Result<(), String> {
+        if self.state.is_latched() {
+            Ok(())
+        } else {
+            Err(format!("{} is not latched, nothing to reset", self.state))
+        }
+    }
+
+    /// Moves to `to`, returning the record to log, or `None` if nothing changed.
+    pub fn transition(&mut self, to: DriverState, reason: impl Into<String>) -> Option<Transition> {
+        if self.state == to {
+            return None;
+        }
+        let transition = Transition {
+            at: SystemTime::now(),
+            from: self.state,
+            to,
+            reason: reason.into(),
+        };
+        self.state = to;
+        Some(transition)
+    }
+}
@@ -0,0 +1,23 @@
+use std::time::SystemTime;
+
+use crate::protocol::{LogLevel, RobotTelemetry};
+
+/// A decoded telemetry frame with the robot's timestamp and, once the clocks are
+/// synced, the matching ground-station time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetrySample {
+    pub robot_us: u64,
+    pub ground_time: Option<SystemTime>,
+    pub telemetry: RobotTelemetry,
+}
+
+/// A firmware log line, stamped with ground-station time: mapped from the robot's
+/// clock once synced, otherwise when it arrived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub robot_us: u64,
+    pub at: SystemTime,
+    pub level: LogLevel,
+    pub tag: String,
+    pub text: String,
+}
@@ -0,0 +1,117 @@
+//! Byte transports the driver runs the robot protocol over.
+//!
+//! Everything above the [`Link`] — framing, state machine, retries — is the same
+//! whether the robot is on a USB serial port or behind a Wi-Fi bridge.
+
+mod serial;
+mod tcp;
+mod udp;
+
+use std::{fmt, io, net::{SocketAddr, ToSocketAddrs}, time::Duration};
+
+pub use serial::{DriverPort, LineSetting, LineSettings};
+pub use tcp::TcpTransport;
+pub use udp::UdpTransport;
+
+use tokio::task;
+
+use super::{DriverError, Link};
+
+/// How long the reader thread blocks before checking whether the link was dropped.
+const READ_TIMEOUT: Duration = Duration::from_millis(50);
+/// How long a write may stall before the link counts as lost.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Somewhere the driver can open a [`Link`] to the robot, again after every loss.
+pub trait Transport: fmt::Debug + fmt::Display + Send + Sync {
+    /// Endpoint the driver is selected by: a device path, `tcp://host:port` or `udp://host:port`.
+    fn name(&self) -> &str;
+
+    /// Label the driver's events are tagged with.
+    fn short_name(&self) -> &str {
+        self.name()
+    }
+
+    fn open(&self) -> io::Result<Link>;
+
+    /// Whether the endpoint is there to reconnect to; only serial devices can vanish.
+    fn is_present(&self) -> bool {
+        true
+    }
+
+    /// Applies serial line settings; network transports have none.
+    fn set_line(&mut self, _line: LineSettings) {}
+
+    fn box_clone(&self) -> Box<dyn Transport>;
+}
+
+impl Clone for Box<dyn Transport> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Opens `transport` on the blocking pool: resolving and connecting a network
+/// endpoint can take seconds, which would stall the runtime thread.
+pub async fn open_link(transport: &dyn Transport) -> io::Result<Link> {
+    let transport = transport.box_clone();
+    task::spawn_blocking(move || transport.open())
+        .await
+        .map_err(io::Error::other)?
+}
+
+/// Network transports an address can be entered for on the control panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetProtocol {
+    Tcp,
+    Udp,
+}
+
+impl NetProtocol {
+    /// The endpoint string [`transport_for`] accepts for `address`.
+    pub fn endpoint(&self, address: &str) -> String {
+        match self {
+            NetProtocol::Tcp => format!("tcp://{}", address),
+            NetProtocol::Udp => format!("udp://{}", address),
+        }
+    }
+}
+
+impl fmt::Display for NetProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetProtocol::Tcp => write!(f, "TCP"),
+            NetProtocol::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+/// Builds the transport for an endpoint; anything without a `tcp://` or `udp://`
+/// scheme is taken as a serial device.
+pub fn transport_for(endpoint: &str, line: LineSettings) -> Result<Box<dyn Transport>, DriverError> {
+    if let Some(address) = endpoint.strip_prefix("tcp://") {
+        return Ok(Box::new(TcpTransport::new(address)?));
+    }
+    if let Some(address) = endpoint.strip_prefix("udp://") {
+        return Ok(Box::new(UdpTransport::new(address)?));
+    }
+    Ok(Box::new(DriverPort::new(endpoint.to_string())?.with_line(line)))
+}
+
+/// Checks the shape of a `host:port` address without a blocking DNS lookup.
+fn check_address(address: &str) -> Result<(), DriverError> {
+    let valid = address.rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok_and(|port| port != 0));
+    if valid {
+        Ok(())
+    } else {
+        Err(DriverError::InvalidAddress(address.to_string()))
+    }
+}
+
+fn resolve(address: &str) -> io::Result<SocketAddr> {
+    address.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} did not resolve", address)))
+}
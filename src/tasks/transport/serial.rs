@@ -0,0 +1,175 @@
+use std::{fmt, io, path::Path};
+
+use serialport::{available_ports, ClearBuffer, DataBits, Error as SerialPortError, FlowControl, Parity, SerialPort, SerialPortBuilder, StopBits};
+
+use crate::tasks::{DriverError, Link};
+
+use super::{Transport, READ_TIMEOUT};
+
+/// Serial line parameters applied whenever the driver opens its port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSettings {
+    pub baud: u32,
+    pub data_bits: DataBits,
+    pub flow_control: FlowControl,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+}
+
+impl Default for LineSettings {
+    fn default() -> Self {
+        Self {
+            baud: 115200,
+            data_bits: DataBits::Eight,
+            flow_control: FlowControl::None,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+        }
+    }
+}
+
+impl LineSettings {
+    pub fn apply(&mut self, setting: LineSetting) {
+        match setting {
+            LineSetting::Baud(baud) => self.baud = baud,
+            LineSetting::DataBits(data_bits) => self.data_bits = data_bits,
+            LineSetting::FlowControl(flow_control) => self.flow_control = flow_control,
+            LineSetting::Parity(parity) => self.parity = parity,
+            LineSetting::StopBits(stop_bits) => self.stop_bits = stop_bits,
+        }
+    }
+}
+
+impl fmt::Display for LineSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        write!(f, "{} {}{}{}, flow control {}",
+            self.baud, u8::from(self.data_bits), parity, u8::from(self.stop_bits), self.flow_control)
+    }
+}
+
+/// A single change to [`LineSettings`], as picked from the control panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineSetting {
+    Baud(u32),
+    DataBits(DataBits),
+    FlowControl(FlowControl),
+    Parity(Parity),
+    StopBits(StopBits),
+}
+
+#[derive(Debug, Clone)]
+pub struct DriverPort {
+    port: String,
+    line: LineSettings,
+}
+
+impl DriverPort {
+    /// Accepts enumerated ports as well as device paths that exist but are not
+    /// enumerated, such as pseudo-terminals.
+    pub fn new(port: String) -> Result<Self, DriverError>  {
+        let ports = available_ports()?;
+        if !ports.iter().any(|info| *info.port_name == port) && !Path::new(&port).exists() {
+            return Err(DriverError::InvalidPort);
+        }
+        Ok(
+            Self { 
+                port, 
+                line: LineSettings::default(),
+            }
+        )
+    }
+    
+    pub fn with_baud(mut self, baud: u32) -> Self {
+        self.line.baud = baud;
+        self
+    }
+
+    pub fn with_line(mut self, line: LineSettings) -> Self {
+        self.line = line;
+        self
+    }
+
+    pub fn get_ports() -> Result<Vec<String>, DriverError> {
+        let ports = available_ports()?;
+        Ok(ports.iter().map( |port| port.port_name.to_string()).collect())
+    }
+
+    pub fn to_serial_port(&self) -> SerialPortBuilder {
+        // serialport treats a zero baud rate as "pseudo-terminal" and skips the line
+        // ioctls that PTYs reject.
+        let baud = if self.is_pseudo_terminal() { 0 } else { self.line.baud };
+        serialport::new(&self.port, baud)
+            .data_bits(self.line.data_bits)
+            .flow_control(self.line.flow_control)
+            .parity(self.line.parity)
+            .stop_bits(self.line.stop_bits)
+    }
+
+    /// Opens the port with the driver's read timeout and flushes stale bytes.
+    pub fn open(&self) -> Result<Box<dyn SerialPort>, SerialPortError> {
+        let port = self.to_serial_port().timeout(READ_TIMEOUT).open()?;
+        port.clear(ClearBuffer::All)?;
+        Ok(port)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.port
+    }
+
+    /// Last component of the port path, e.g. `ttyUSB0`, used to name its driver.
+    pub fn short_name(&self) -> &str {
+        Path::new(&self.port).file_name().and_then(|name| name.to_str()).unwrap_or(&self.port)
+    }
+
+    pub fn is_pseudo_terminal(&self) -> bool {
+        self.port.starts_with("/dev/pts/") || self.port.starts_with("/dev/ttys")
+    }
+
+    /// Whether the port is currently listed by the OS or its device node exists.
+    pub fn is_present(&self) -> bool {
+        let listed = match available_ports() {
+            Ok(ports) => ports.iter().any(|info| info.port_name == self.port),
+            Err(_) => false,
+        };
+        listed || Path::new(&self.port).exists()
+    }
+}
+
+impl fmt::Display for DriverPort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} @ {}", self.port, self.line)
+    }
+}
+
+
+impl Transport for DriverPort {
+    fn name(&self) -> &str {
+        DriverPort::name(self)
+    }
+
+    fn short_name(&self) -> &str {
+        DriverPort::short_name(self)
+    }
+
+    fn open(&self) -> io::Result<Link> {
+        let port = DriverPort::open(self)?;
+        Ok(Link::new(port.try_clone()?, port))
+    }
+
+    fn is_present(&self) -> bool {
+        DriverPort::is_present(self)
+    }
+
+    fn set_line(&mut self, line: LineSettings) {
+        self.line = line;
+    }
+
+    fn box_clone(&self) -> Box<dyn Transport> {
+        Box::new(self.clone())
+    }
+}
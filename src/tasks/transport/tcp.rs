@@ -0,0 +1,49 @@
+use std::{fmt, io, net::TcpStream};
+
+use crate::tasks::{DriverError, Link};
+
+use super::{check_address, resolve, Transport, CONNECT_TIMEOUT, READ_TIMEOUT, WRITE_TIMEOUT};
+
+/// Robot reached through a TCP server, such as a Wi-Fi serial bridge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpTransport {
+    endpoint: String,
+}
+
+impl TcpTransport {
+    /// Takes a `host:port` address; it is resolved again on every connect.
+    pub fn new(address: &str) -> Result<Self, DriverError> {
+        check_address(address)?;
+        Ok(Self { endpoint: format!("tcp://{}", address) })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.endpoint["tcp://".len()..]
+    }
+}
+
+impl Transport for TcpTransport {
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn open(&self) -> io::Result<Link> {
+        let stream = TcpStream::connect_timeout(&resolve(self.address())?, CONNECT_TIMEOUT)?;
+        // Frames are small and latency matters more than packing them together.
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        // A stalled peer must not block the writer thread, and so closing the link, for good.
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        Ok(Link::new(stream.try_clone()?, stream))
+    }
+
+    fn box_clone(&self) -> Box<dyn Transport> {
+        Box::new(self.clone())
+    }
+}
+
+impl fmt::Display for TcpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.endpoint)
+    }
+}
@@ -0,0 +1,94 @@
+use std::{fmt, io::{self, Read, Write}, net::{SocketAddr, UdpSocket}};
+
+use crate::tasks::{DriverError, Link};
+
+use super::{check_address, resolve, Transport, READ_TIMEOUT, WRITE_TIMEOUT};
+
+const MAX_DATAGRAM: usize = 65536;
+
+/// Robot reached by UDP datagrams, one or more frames per datagram.
+///
+/// UDP has no connection to lose, so the link only drops when the OS reports the
+/// robot's port unreachable; silence is left to the link statistics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdpTransport {
+    endpoint: String,
+}
+
+impl UdpTransport {
+    /// Takes a `host:port` address; it is resolved again on every connect.
+    pub fn new(address: &str) -> Result<Self, DriverError> {
+        check_address(address)?;
+        Ok(Self { endpoint: format!("udp://{}", address) })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.endpoint["udp://".len()..]
+    }
+}
+
+impl Transport for UdpTransport {
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn open(&self) -> io::Result<Link> {
+        let peer = resolve(self.address())?;
+        let local: SocketAddr = if peer.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+        let socket = UdpSocket::bind(local)?;
+        // Connecting filters out datagrams from anyone but the robot.
+        socket.connect(peer)?;
+        socket.set_read_timeout(Some(READ_TIMEOUT))?;
+        socket.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        Ok(Link::new(Datagrams::new(socket.try_clone()?), Datagrams::new(socket)))
+    }
+
+    fn box_clone(&self) -> Box<dyn Transport> {
+        Box::new(self.clone())
+    }
+}
+
+impl fmt::Display for UdpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.endpoint)
+    }
+}
+
+/// A connected socket read and written a datagram at a time.
+struct Datagrams {
+    socket: UdpSocket,
+    /// Rest of a datagram larger than the caller's buffer.
+    pending: Vec<u8>,
+}
+
+impl Datagrams {
+    fn new(socket: UdpSocket) -> Self {
+        Self { socket, pending: Vec::new() }
+    }
+}
+
+impl Read for Datagrams {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // An empty datagram is not end of stream, so wait for one with bytes in it.
+        while self.pending.is_empty() {
+            let mut datagram = vec![0u8; MAX_DATAGRAM];
+            let count = self.socket.recv(&mut datagram)?;
+            datagram.truncate(count);
+            self.pending = datagram;
+        }
+        let count = buf.len().min(self.pending.len());
+        buf[..count].copy_from_slice(&self.pending[..count]);
+        self.pending.drain(..count);
+        Ok(count)
+    }
+}
+
+impl Write for Datagrams {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.socket.send(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
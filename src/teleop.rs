@@ -0,0 +1,13 @@
+mod bindings;
+mod conditioning;
+mod drive_input;
+mod keyboard;
+mod kinematics;
+mod pad_state;
+
+pub use bindings::{Action, ActionEvent, ActionMapper, BindingAction, BindingError, Bindings, Input, Profile, BINDINGS_FILE};
+pub use conditioning::{AxisConditioning, AxisState, Curve, Stage};
+pub use drive_input::{DriveInput, INTAKE_BIT};
+pub use keyboard::{key_help, KeyboardDrive, DEFAULT_RAMP, KEY_HOLD, RAMP_STEP};
+pub use kinematics::{arcade, curvature, mecanum, tank, DriveAxes, DriveMode, Kinematics, Wheels};
+pub use pad_state::{PadState, RATE_WINDOW};
@@ -0,0 +1,498 @@
+use std::{collections::HashMap, fmt, fs, path::{Path, PathBuf}, str::FromStr, time::SystemTime};
+
+use gilrs::{Axis, Button, EventType as GamepadEventType};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use super::{AxisConditioning, AxisState, Stage};
+
+/// Where bindings are read from and saved to, relative to the working directory.
+pub const BINDINGS_FILE: &str = "bindings.conf";
+/// Section for pads without a profile of their own.
+const DEFAULT_PROFILE: &str = "default";
+/// How far an axis bound to a button action has to move to press it.
+const AXIS_PRESS: f32 = 0.5;
+
+/// What the operator can ask of the robot, whatever pad they hold.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    DriveX,
+    DriveY,
+    DriveTurn,
+    ArmLift,
+    /// Right side throttle in tank drive.
+    TankRight,
+    /// Held to turn on the spot in curvature drive.
+    QuickTurn,
+    IntakeToggle,
+    Estop,
+}
+
+impl Action {
+    /// Whether the action takes a value rather than being pressed.
+    pub fn is_axis(&self) -> bool {
+        matches!(self, Action::DriveX | Action::DriveY | Action::DriveTurn | Action::ArmLift | Action::TankRight)
+    }
+}
+
+/// A physical stick axis or button on the pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Input {
+    Axis(Axis),
+    Button(Button),
+    /// Two buttons acting as an axis: the first pushes it to `1.0`, the second to `-1.0`.
+    /// gilrs reports the d-pad as buttons, so this is how it drives an axis action.
+    ButtonPair(Button, Button),
+}
+
+const AXES: [Axis; 8] = [
+    Axis::LeftStickX, Axis::LeftStickY, Axis::LeftZ,
+    Axis::RightStickX, Axis::RightStickY, Axis::RightZ,
+    Axis::DPadX, Axis::DPadY,
+];
+
+const BUTTONS: [Button; 19] = [
+    Button::South, Button::East, Button::North, Button::West, Button::C, Button::Z,
+    Button::LeftTrigger, Button::LeftTrigger2, Button::RightTrigger, Button::RightTrigger2,
+    Button::Select, Button::Start, Button::Mode, Button::LeftThumb, Button::RightThumb,
+    Button::DPadUp, Button::DPadDown, Button::DPadLeft, Button::DPadRight,
+];
+
+impl Input {
+    /// The input a captured event names: any button press, or an axis pushed well off centre.
+    pub fn captured(event: &GamepadEventType) -> Option<Self> {
+        match *event {
+            GamepadEventType::ButtonPressed(button, _) if button != Button::Unknown => Some(Input::Button(button)),
+            GamepadEventType::AxisChanged(axis, value, _) if axis != Axis::Unknown && value.abs() > AXIS_PRESS => Some(Input::Axis(axis)),
+            _ => None,
+        }
+    }
+
+    fn buttons(&self) -> Vec<Button> {
+        match *self {
+            Input::Axis(_) => Vec::new(),
+            Input::Button(button) => vec![button],
+            Input::ButtonPair(up, down) => vec![up, down],
+        }
+    }
+
+    /// Whether the two inputs share an axis or a button.
+    pub fn overlaps(&self, other: &Input) -> bool {
+        match (self, other) {
+            (Input::Axis(axis), Input::Axis(other)) => axis == other,
+            _ => self.buttons().iter().any(|button| other.buttons().contains(button)),
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Axis(axis) => write!(f, "axis {:?}", axis),
+            Input::Button(button) => write!(f, "button {:?}", button),
+            Input::ButtonPair(up, down) => write!(f, "buttons {:?} {:?}", up, down),
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    /// Parses `axis LeftStickX`, `button South` or `buttons DPadUp DPadDown`, as written
+    /// by [`fmt::Display`].
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (kind, name) = text.split_once(char::is_whitespace).ok_or_else(|| format!("'{}' is not 'axis <name>' or 'button <name>'", text))?;
+        let name = name.trim();
+        let button = |name: &str| BUTTONS.iter().find(|button| format!("{:?}", button) == name).copied()
+            .ok_or_else(|| format!("unknown button '{}'", name));
+        match kind {
+            "axis" => AXES.iter().find(|axis| format!("{:?}", axis) == name).map(|axis| Input::Axis(*axis))
+                .ok_or_else(|| format!("unknown axis '{}'", name)),
+            "button" => button(name).map(Input::Button),
+            "buttons" => match name.split_whitespace().collect::<Vec<&str>>()[..] {
+                [up, down] if up != down => Ok(Input::ButtonPair(button(up)?, button(down)?)),
+                _ => Err(format!("'{}' is not two different buttons", name)),
+            },
+            _ => Err(format!("unknown input kind '{}'", kind)),
+        }
+    }
+}
+
+/// Which input drives each action on one pad, and how axis actions are conditioned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    bindings: HashMap<Action, Input>,
+    conditioning: HashMap<Action, AxisConditioning>,
+}
+
+impl Default for Profile {
+    /// Left stick drives, right stick turns (and drives the right side in tank), the
+    /// d-pad lifts the arm, the right bumper quick-turns, A toggles the intake and
+    /// Select e-stops. No input drives two axis actions, so driving never moves the arm.
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from([
+                (Action::DriveX, Input::Axis(Axis::LeftStickX)),
+                (Action::DriveY, Input::Axis(Axis::LeftStickY)),
+                (Action::DriveTurn, Input::Axis(Axis::RightStickX)),
+                (Action::ArmLift, Input::ButtonPair(Button::DPadUp, Button::DPadDown)),
+                (Action::TankRight, Input::Axis(Axis::RightStickY)),
+                (Action::QuickTurn, Input::Button(Button::RightTrigger)),
+                (Action::IntakeToggle, Input::Button(Button::South)),
+                (Action::Estop, Input::Button(Button::Select)),
+            ]),
+            conditioning: HashMap::new(),
+        }
+    }
+}
+
+impl Profile {
+    pub fn input(&self, action: Action) -> Option<Input> {
+        self.bindings.get(&action).copied()
+    }
+
+    /// Binds `action` to `input`. An axis action takes the input over from any other
+    /// axis action bound to it, which is left unbound and returned.
+    pub fn bind(&mut self, action: Action, input: Input) -> Option<Action> {
+        let displaced = self.axis_conflict(action, input);
+        if let Some(other) = displaced {
+            self.bindings.remove(&other);
+        }
+        self.bindings.insert(action, input);
+        displaced
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+
+    pub fn conditioning(&self, action: Action) -> AxisConditioning {
+        self.conditioning.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn set_conditioning(&mut self, action: Action, conditioning: AxisConditioning) {
+        if conditioning == AxisConditioning::default() {
+            self.conditioning.remove(&action);
+        } else {
+            self.conditioning.insert(action, conditioning);
+        }
+    }
+
+    /// The other axis action whose input overlaps `input`, which binding the axis
+    /// action `action` to it would clash with.
+    pub fn axis_conflict(&self, action: Action, input: Input) -> Option<Action> {
+        if !action.is_axis() {
+            return None;
+        }
+        Action::iter()
+            .filter(|other| other.is_axis() && *other != action)
+            .find(|other| self.input(*other).is_some_and(|bound| bound.overlaps(&input)))
+    }
+
+    /// Actions bound to `input`, in [`Action`] order.
+    pub fn actions_for(&self, input: Input) -> impl Iterator<Item = Action> + '_ {
+        Action::iter().filter(move |action| self.bindings.get(action) == Some(&input))
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingError {
+    Io(String),
+    /// A line that could not be used, with its 1-based number.
+    Parse { line: usize, reason: String },
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingError::Io(e) => write!(f, "Couldn't access bindings: {}", e),
+            BindingError::Parse { line, reason } => write!(f, "Bad binding on line {}: {}", line, reason),
+        }
+    }
+}
+
+impl std::error::Error for BindingError {}
+
+/// Controller profiles keyed by the pad's UUID in hex, with a default for the rest.
+///
+/// On disk each profile is a `[uuid]` or `[default]` section of `action = axis Name`
+/// or `action = button Name` lines, and `action.stage = value` lines for the axis
+/// conditioning stages that aren't at their defaults; `#` starts a comment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bindings {
+    default: Profile,
+    profiles: HashMap<String, Profile>,
+}
+
+impl Bindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the bindings file, falling back to the defaults when there is none.
+    pub fn load(path: &Path) -> Result<Self, BindingError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(BindingError::Io(e.to_string())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BindingError> {
+        fs::write(path, self.to_text()).map_err(|e| BindingError::Io(e.to_string()))
+    }
+
+    /// The default path, [`BINDINGS_FILE`] in the working directory.
+    pub fn path() -> PathBuf {
+        PathBuf::from(BINDINGS_FILE)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, BindingError> {
+        let mut bindings = Self::default();
+        // Sections list their bindings in full, so start from nothing rather than the defaults.
+        let mut section: Option<&mut Profile> = None;
+        for (index, line) in text.lines().enumerate() {
+            let error = |reason: String| BindingError::Parse { line: index + 1, reason };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                let name = name.trim().to_ascii_lowercase();
+                let profile = if name == DEFAULT_PROFILE {
+                    &mut bindings.default
+                } else if name.len() == 32 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+                    bindings.profiles.entry(name).or_insert_with(Profile::default)
+                } else {
+                    return Err(error(format!("'{}' is not a controller UUID or 'default'", name)));
+                };
+                profile.bindings.clear();
+                profile.conditioning.clear();
+                section = Some(profile);
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| error("expected 'action = input'".to_string()))?;
+            let (action, stage) = match key.trim().split_once('.') {
+                Some((action, stage)) => (action, Some(stage)),
+                None => (key.trim(), None),
+            };
+            let action = Action::from_str(action).map_err(|_| error(format!("unknown action '{}'", action)))?;
+            let profile = section.as_deref_mut()
+                .ok_or_else(|| error("binding before any [profile] section".to_string()))?;
+            match stage {
+                None => {
+                    let input = Input::from_str(value.trim()).map_err(error)?;
+                    if let Some(other) = profile.axis_conflict(action, input) {
+                        return Err(error(format!("{} already drives {}", input, other)));
+                    }
+                    profile.bind(action, input);
+                },
+                Some(_) if !action.is_axis() => return Err(error(format!("{} is not an axis action", action))),
+                Some(stage) => {
+                    let stage = Stage::from_str(stage).map_err(|_| error(format!("unknown stage '{}'", stage)))?;
+                    let mut conditioning = profile.conditioning(action);
+                    conditioning.set(stage, value).map_err(error)?;
+                    profile.set_conditioning(action, conditioning);
+                },
+            }
+        }
+        Ok(bindings)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut names: Vec<&String> = self.profiles.keys().collect();
+        names.sort();
+        let sections = std::iter::once((DEFAULT_PROFILE, &self.default))
+            .chain(names.into_iter().map(|name| (name.as_str(), &self.profiles[name])));
+        let mut text = String::new();
+        for (name, profile) in sections {
+            text.push_str(&format!("[{}]\n", name));
+            for action in Action::iter() {
+                if let Some(input) = profile.input(action) {
+                    text.push_str(&format!("{} = {}\n", action, input));
+                }
+                let conditioning = profile.conditioning(action);
+                let default = AxisConditioning::default();
+                for stage in Stage::iter().filter(|stage| conditioning.get(*stage) != default.get(*stage)) {
+                    text.push_str(&format!("{}.{} = {}\n", action, stage, conditioning.get(stage)));
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The profile for a pad, or the default when it has none.
+    pub fn profile(&self, uuid: &str) -> &Profile {
+        self.profiles.get(uuid).unwrap_or(&self.default)
+    }
+
+    pub fn has_profile(&self, uuid: &str) -> bool {
+        self.profiles.contains_key(uuid)
+    }
+
+    /// The pad's own profile to edit, started as a copy of the default; without a pad
+    /// the default itself.
+    pub fn profile_mut(&mut self, uuid: Option<&str>) -> &mut Profile {
+        let Some(uuid) = uuid else {
+            return &mut self.default;
+        };
+        let default = &self.default;
+        self.profiles.entry(uuid.to_string()).or_insert_with(|| default.clone())
+    }
+
+    /// Binds the pad's `action` to `input`, returning the axis action that lost it.
+    pub fn rebind(&mut self, uuid: Option<&str>, action: Action, input: Input) -> Option<Action> {
+        self.profile_mut(uuid).bind(action, input)
+    }
+}
+
+/// What a gamepad event meant in terms of [`Action`]s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActionEvent {
+    Axis(Action, f32),
+    Pressed(Action),
+    Released(Action),
+}
+
+/// Turns the pad's event stream into action events through a [`Profile`], conditioning
+/// axis actions on the way.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionMapper {
+    profile: Profile,
+    /// Button actions currently held, so an axis crossing the threshold presses once.
+    held: Vec<Action>,
+    /// Latest raw value of every pad axis, for radial deadzones.
+    axes: HashMap<Axis, f32>,
+    /// Latest value of every pad button, for button pairs.
+    buttons: HashMap<Button, f32>,
+    states: HashMap<Action, AxisState>,
+    last_step: Option<SystemTime>,
+}
+
+impl ActionMapper {
+    pub fn new(profile: Profile) -> Self {
+        Self { profile, ..Self::default() }
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = profile;
+        self.held.clear();
+        self.retarget();
+    }
+
+    /// Redoes the time-independent stages for every axis action.
+    fn retarget(&mut self) {
+        for action in Action::iter().filter(Action::is_axis) {
+            let state = self.states.entry(action).or_default();
+            let magnitude = match self.profile.input(action) {
+                Some(Input::Axis(axis)) => stick_magnitude(&self.axes, axis),
+                _ => state.raw.abs(),
+            };
+            state.target = self.profile.conditioning(action).shape(state.raw, magnitude);
+        }
+    }
+
+    /// Forgets every input and zeroes every axis action without ramping, so a pad that
+    /// is gone leaves nothing for [`ActionMapper::tick`] to keep driving on.
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.axes.clear();
+        self.buttons.clear();
+        self.states.clear();
+        self.last_step = None;
+    }
+
+    /// Where an axis action is in its conditioning pipeline.
+    pub fn state(&self, action: Action) -> AxisState {
+        self.states.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn map(&mut self, event: &GamepadEventType, time: SystemTime) -> Vec<ActionEvent> {
+        let (input, value) = match *event {
+            GamepadEventType::Disconnected => {
+                self.reset();
+                return Vec::new();
+            },
+            GamepadEventType::AxisChanged(axis, value, _) => (Input::Axis(axis), value),
+            GamepadEventType::ButtonPressed(button, _) => (Input::Button(button), 1.0),
+            GamepadEventType::ButtonReleased(button, _) => (Input::Button(button), 0.0),
+            _ => return Vec::new(),
+        };
+        self.map_input(input, value, time)
+    }
+
+    /// Maps an input's new value, `1.0` or `0.0` for a button, as of `time`.
+    pub fn map_input(&mut self, input: Input, value: f32, time: SystemTime) -> Vec<ActionEvent> {
+        if let Input::Axis(axis) = input {
+            self.axes.insert(axis, value);
+        }
+        let mut actions: Vec<(Action, f32)> = self.profile.actions_for(input).map(|action| (action, value)).collect();
+        if let Input::Button(button) = input {
+            self.buttons.insert(button, value);
+            let held = |button| self.buttons.get(&button).copied().unwrap_or_default();
+            actions.extend(Action::iter().filter_map(|action| match self.profile.input(action) {
+                Some(Input::ButtonPair(up, down)) if button == up || button == down => Some((action, held(up) - held(down))),
+                _ => None,
+            }));
+        }
+        let mut events: Vec<ActionEvent> = actions.into_iter().filter_map(|(action, value)| {
+            if action.is_axis() {
+                self.states.entry(action).or_default().raw = value;
+                return None;
+            }
+            let pressed = value.abs() > AXIS_PRESS;
+            let was_held = self.held.contains(&action);
+            match (pressed, was_held) {
+                (true, false) => {
+                    self.held.push(action);
+                    Some(ActionEvent::Pressed(action))
+                },
+                (false, true) => {
+                    self.held.retain(|held| *held != action);
+                    Some(ActionEvent::Released(action))
+                },
+                _ => None,
+            }
+        }).collect();
+        // Any stick move can shift a radial deadzone on its partner axis, so every target is redone.
+        self.retarget();
+        events.extend(self.tick(time));
+        events
+    }
+
+    /// Advances the filter and slew stages to `time`, reporting outputs that moved.
+    pub fn tick(&mut self, time: SystemTime) -> Vec<ActionEvent> {
+        let elapsed = self.last_step.and_then(|last| time.duration_since(last).ok()).unwrap_or_default();
+        self.last_step = Some(time);
+        Action::iter().filter(Action::is_axis).filter_map(|action| {
+            let conditioning = self.profile.conditioning(action);
+            let state = self.states.entry(action).or_default();
+            let before = state.output;
+            conditioning.step(state, elapsed);
+            (state.output != before).then_some(ActionEvent::Axis(action, state.output))
+        }).collect()
+    }
+}
+
+/// How far the stick `axis` belongs to is deflected, over both of its axes.
+fn stick_magnitude(axes: &HashMap<Axis, f32>, axis: Axis) -> f32 {
+    let value = axes.get(&axis).copied().unwrap_or_default();
+    let other = axis.second_axis().and_then(|other| axes.get(&other).copied()).unwrap_or_default();
+    value.hypot(other)
+}
+
+/// Control-panel actions on the bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingAction {
+    /// Binds the action to the next input moved or pressed on the pad.
+    Capture(Action),
+    CancelCapture,
+    Save,
+    Reload,
+}
@@ -0,0 +1,204 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// Below this the filter and slew stages are considered to have caught up.
+const SETTLED: f32 = 1e-3;
+
+/// How stick deflection maps to output before scaling.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Curve {
+    #[default]
+    Linear,
+    /// `(e^(k|x|) - 1) / (e^k - 1)`, softer near centre as `k` grows.
+    Expo(f32),
+    /// `(1 - w)x + wx³`, blending linear with a cube by `w` in `0..=1`.
+    Cubic(f32),
+}
+
+impl Curve {
+    pub fn apply(&self, x: f32) -> f32 {
+        match *self {
+            Curve::Linear => x,
+            Curve::Expo(k) if k.abs() < f32::EPSILON => x,
+            Curve::Expo(k) => x.signum() * (k * x.abs()).exp_m1() / k.exp_m1(),
+            Curve::Cubic(w) => (1.0 - w) * x + w * x * x * x,
+        }
+    }
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Curve::Linear => write!(f, "linear"),
+            Curve::Expo(k) => write!(f, "expo {}", k),
+            Curve::Cubic(w) => write!(f, "cubic {}", w),
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    /// Parses `linear`, `expo <k>` or `cubic <w>`.
+    fn from_str(text: &str) -> Result<Self, String> {
+        let mut words = text.split_whitespace();
+        let kind = words.next().unwrap_or("");
+        let amount = words.next().map(|word| {
+            word.parse::<f32>().ok().filter(|value| value.is_finite()).ok_or_else(|| format!("'{}' is not a number", word))
+        });
+        match (kind, amount) {
+            ("linear", None) => Ok(Curve::Linear),
+            ("expo", Some(k)) => Ok(Curve::Expo(k?)),
+            ("cubic", Some(w)) => {
+                let w = w?;
+                if (0.0..=1.0).contains(&w) {
+                    Ok(Curve::Cubic(w))
+                } else {
+                    Err("cubic weight must be between 0 and 1".to_string())
+                }
+            },
+            _ => Err(format!("'{}' is not 'linear', 'expo <k>' or 'cubic <w>'", text)),
+        }
+    }
+}
+
+/// One stage of [`AxisConditioning`], as named in the bindings file and on the control panel.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, EnumIter, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Stage {
+    Radial,
+    Deadzone,
+    Curve,
+    Invert,
+    Scale,
+    Filter,
+    Slew,
+}
+
+/// Shapes one axis action, in order: radial deadzone over the whole stick, deadzone
+/// on the axis alone, response curve, inversion, scaling, low-pass filter and slew limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisConditioning {
+    /// Stick deflection, over both of its axes, that still reads as centred.
+    pub radial: f32,
+    pub deadzone: f32,
+    pub curve: Curve,
+    pub invert: bool,
+    pub scale: f32,
+    /// Low-pass time constant in seconds; zero passes input straight through.
+    pub filter: f32,
+    /// Most the output may change per second; zero for no limit.
+    pub slew: f32,
+}
+
+impl Default for AxisConditioning {
+    fn default() -> Self {
+        Self {
+            radial: 0.0,
+            deadzone: 0.0,
+            curve: Curve::Linear,
+            invert: false,
+            scale: 1.0,
+            filter: 0.0,
+            slew: 0.0,
+        }
+    }
+}
+
+impl AxisConditioning {
+    /// The stages that don't depend on time, for an axis whose stick reads `magnitude` overall.
+    pub fn shape(&self, value: f32, magnitude: f32) -> f32 {
+        let value = if magnitude <= self.radial {
+            0.0
+        } else if self.radial > 0.0 {
+            // Rescale so output still starts from zero at the edge of the deadzone.
+            value * (magnitude - self.radial) / ((1.0 - self.radial) * magnitude)
+        } else {
+            value
+        };
+        let value = if value.abs() <= self.deadzone {
+            0.0
+        } else {
+            value.signum() * (value.abs() - self.deadzone) / (1.0 - self.deadzone)
+        };
+        let value = self.curve.apply(value.clamp(-1.0, 1.0));
+        let value = if self.invert { -value } else { value };
+        value * self.scale
+    }
+
+    /// Moves `output` towards `target` over `elapsed` through the filter and slew limit.
+    pub fn step(&self, state: &mut AxisState, elapsed: Duration) {
+        let dt = elapsed.as_secs_f32();
+        state.filtered = if self.filter > 0.0 {
+            state.filtered + (state.target - state.filtered) * (1.0 - (-dt / self.filter).exp())
+        } else {
+            state.target
+        };
+        if (state.filtered - state.target).abs() < SETTLED {
+            state.filtered = state.target;
+        }
+        state.output = if self.slew > 0.0 {
+            let step = self.slew * dt;
+            state.output + (state.filtered - state.output).clamp(-step, step)
+        } else {
+            state.filtered
+        };
+        if (state.output - state.filtered).abs() < SETTLED {
+            state.output = state.filtered;
+        }
+    }
+
+    pub fn get(&self, stage: Stage) -> String {
+        match stage {
+            Stage::Radial => self.radial.to_string(),
+            Stage::Deadzone => self.deadzone.to_string(),
+            Stage::Curve => self.curve.to_string(),
+            Stage::Invert => self.invert.to_string(),
+            Stage::Scale => self.scale.to_string(),
+            Stage::Filter => self.filter.to_string(),
+            Stage::Slew => self.slew.to_string(),
+        }
+    }
+
+    /// Sets one stage from text, checking it is in range.
+    pub fn set(&mut self, stage: Stage, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        let number = || text.parse::<f32>().ok().filter(|value| value.is_finite()).ok_or_else(|| format!("'{}' is not a number", text));
+        let fraction = || number().and_then(|value| {
+            if (0.0..1.0).contains(&value) { Ok(value) } else { Err(format!("{} must be at least 0 and below 1", stage)) }
+        });
+        let non_negative = || number().and_then(|value| {
+            if value >= 0.0 { Ok(value) } else { Err(format!("{} can't be negative", stage)) }
+        });
+        match stage {
+            Stage::Radial => self.radial = fraction()?,
+            Stage::Deadzone => self.deadzone = fraction()?,
+            Stage::Curve => self.curve = text.parse()?,
+            Stage::Invert => self.invert = text.parse().map_err(|_| format!("'{}' is not true or false", text))?,
+            Stage::Scale => self.scale = number()?,
+            Stage::Filter => self.filter = non_negative()?,
+            Stage::Slew => self.slew = non_negative()?,
+        }
+        Ok(())
+    }
+}
+
+/// Where one axis action is in the pipeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AxisState {
+    /// The bound input as the pad reported it.
+    pub raw: f32,
+    /// After the stages that don't depend on time.
+    pub target: f32,
+    filtered: f32,
+    /// What the robot is sent.
+    pub output: f32,
+}
+
+impl AxisState {
+    /// Whether the filter and slew limit still have ground to cover.
+    pub fn is_settled(&self) -> bool {
+        (self.output - self.target).abs() < SETTLED
+    }
+}
@@ -0,0 +1,79 @@
+use crate::protocol::DriveCommand;
+
+use super::{Action, ActionEvent, DriveAxes, Kinematics};
+
+/// Bit set in [`DriveCommand::buttons`] while the intake is toggled on.
+pub const INTAKE_BIT: u32 = 1 << 0;
+
+/// Latest operator input, folded from action events and mixed through the selected
+/// [`Kinematics`] into the command streamed to the robot.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DriveInput {
+    axes: DriveAxes,
+    arm: f32,
+    buttons: u32,
+    kinematics: Kinematics,
+    /// Robot heading from telemetry, for field-oriented drive.
+    heading_deg: Option<f32>,
+}
+
+impl DriveInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies an action event, returning whether the drive command changed.
+    pub fn update(&mut self, event: &ActionEvent) -> bool {
+        let before = self.command();
+        match *event {
+            ActionEvent::Axis(action, value) => match action {
+                Action::DriveX => self.axes.x = value,
+                Action::DriveY => self.axes.y = value,
+                Action::DriveTurn => self.axes.turn = value,
+                Action::TankRight => self.axes.right_y = value,
+                Action::ArmLift => self.arm = value,
+                _ => (),
+            },
+            ActionEvent::Pressed(Action::IntakeToggle) => self.buttons ^= INTAKE_BIT,
+            ActionEvent::Pressed(Action::QuickTurn) => self.axes.quick_turn = true,
+            ActionEvent::Released(Action::QuickTurn) => self.axes.quick_turn = false,
+            ActionEvent::Pressed(_) | ActionEvent::Released(_) => (),
+        }
+        self.command() != before
+    }
+
+    /// Back to sticks centred and everything off, e.g. when the pad goes away.
+    pub fn reset(&mut self) -> bool {
+        let before = self.command();
+        self.axes = DriveAxes::default();
+        self.arm = 0.0;
+        self.buttons = 0;
+        self.command() != before
+    }
+
+    pub fn kinematics(&self) -> Kinematics {
+        self.kinematics
+    }
+
+    /// Changes the mixer, returning whether the drive command changed.
+    pub fn set_kinematics(&mut self, kinematics: Kinematics) -> bool {
+        let before = self.command();
+        self.kinematics = kinematics;
+        self.command() != before
+    }
+
+    /// Updates the heading field-oriented drive works from, returning whether the drive command changed.
+    pub fn set_heading(&mut self, heading_deg: Option<f32>) -> bool {
+        let before = self.command();
+        self.heading_deg = heading_deg;
+        self.command() != before
+    }
+
+    pub fn command(&self) -> DriveCommand {
+        DriveCommand {
+            wheels: self.kinematics.mix(&self.axes, self.heading_deg),
+            arm: self.arm,
+            buttons: self.buttons,
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, time::{Duration, SystemTime}};
+
+use strum::IntoEnumIterator;
+
+use super::{Action, ActionEvent};
+
+/// Without release reports a key counts as held this long after it was last seen,
+/// long enough to bridge the pause before the terminal starts auto-repeating.
+pub const KEY_HOLD: Duration = Duration::from_millis(600);
+/// Ramp rate, per second, that a fresh keyboard drive starts with.
+pub const DEFAULT_RAMP: f32 = 2.0;
+/// How much `[` and `]` change the ramp rate.
+pub const RAMP_STEP: f32 = 0.5;
+const RAMP_LIMITS: (f32, f32) = (0.5, 20.0);
+
+/// Keys that push an axis action, and which way.
+const AXIS_KEYS: [(char, Action, f32); 10] = [
+    ('w', Action::DriveY, 1.0),
+    ('s', Action::DriveY, -1.0),
+    ('d', Action::DriveTurn, 1.0),
+    ('a', Action::DriveTurn, -1.0),
+    ('e', Action::DriveX, 1.0),
+    ('q', Action::DriveX, -1.0),
+    ('r', Action::ArmLift, 1.0),
+    ('f', Action::ArmLift, -1.0),
+    ('o', Action::TankRight, 1.0),
+    ('l', Action::TankRight, -1.0),
+];
+
+/// Keys that stand in for a pad button.
+const BUTTON_KEYS: [(char, Action); 2] = [
+    ('i', Action::IntakeToggle),
+    ('g', Action::QuickTurn),
+];
+
+/// The drive keys and what they do, for the status line.
+pub fn key_help() -> String {
+    let axes = AXIS_KEYS.chunks(2)
+        .map(|keys| format!("{}/{} {}", keys[0].0, keys[1].0, keys[0].1));
+    let buttons = BUTTON_KEYS.iter().map(|(key, action)| format!("{} {}", key, action));
+    axes.chain(buttons).collect::<Vec<String>>().join(", ")
+}
+
+/// Drives from the keyboard when the pad is gone: held keys set each axis action's
+/// target, which the output ramps towards, and hotkeys press button actions. Emits
+/// the same [`ActionEvent`]s a pad does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardDrive {
+    enabled: bool,
+    /// Keys held down, with when each was last seen.
+    held: HashMap<char, SystemTime>,
+    /// Set once the terminal reports a release, after which keys are held until released.
+    releases: bool,
+    /// Most an axis output may change per second.
+    ramp: f32,
+    outputs: HashMap<Action, f32>,
+    last_step: Option<SystemTime>,
+}
+
+impl Default for KeyboardDrive {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            held: HashMap::new(),
+            releases: false,
+            ramp: DEFAULT_RAMP,
+            outputs: HashMap::new(),
+            last_step: None,
+        }
+    }
+}
+
+impl KeyboardDrive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Switches between driving and menu navigation. Leaving drive mode lets go of
+    /// every key and stops at once, without ramping.
+    pub fn set_enabled(&mut self, enabled: bool) -> Vec<ActionEvent> {
+        self.enabled = enabled;
+        if enabled {
+            return Vec::new();
+        }
+        let mut events: Vec<ActionEvent> = self.held.drain()
+            .filter_map(|(key, _)| button(key).map(ActionEvent::Released))
+            .collect();
+        events.extend(self.outputs.drain()
+            .filter(|(_, output)| *output != 0.0)
+            .map(|(action, _)| ActionEvent::Axis(action, 0.0)));
+        self.last_step = None;
+        events
+    }
+
+    /// Tells the drive up front that the terminal reports releases, so held keys last
+    /// until released and auto-repeat isn't needed to keep them down.
+    pub fn set_reports_releases(&mut self, releases: bool) {
+        self.releases = releases;
+    }
+
+    pub fn ramp(&self) -> f32 {
+        self.ramp
+    }
+
+    pub fn set_ramp(&mut self, ramp: f32) -> Result<(), String> {
+        if !(RAMP_LIMITS.0..=RAMP_LIMITS.1).contains(&ramp) {
+            return Err(format!("ramp rate must be between {} and {} per second", RAMP_LIMITS.0, RAMP_LIMITS.1));
+        }
+        self.ramp = ramp;
+        Ok(())
+    }
+
+    /// Whether the key means anything while driving.
+    pub fn is_drive_key(key: char) -> bool {
+        let key = key.to_ascii_lowercase();
+        is_held_key(key) || key == '[' || key == ']'
+    }
+
+    /// A key went down, or repeated.
+    pub fn press(&mut self, key: char, time: SystemTime) -> Vec<ActionEvent> {
+        let key = key.to_ascii_lowercase();
+        if !self.enabled {
+            return Vec::new();
+        }
+        match key {
+            '[' => self.ramp = (self.ramp - RAMP_STEP).max(RAMP_LIMITS.0),
+            ']' => self.ramp = (self.ramp + RAMP_STEP).min(RAMP_LIMITS.1),
+            _ => (),
+        }
+        let mut events = Vec::new();
+        if is_held_key(key) && self.held.insert(key, time).is_none() {
+            events.extend(button(key).map(ActionEvent::Pressed));
+        }
+        events.extend(self.tick(time));
+        events
+    }
+
+    /// A key came up; only terminals that report releases send these.
+    pub fn release(&mut self, key: char, time: SystemTime) -> Vec<ActionEvent> {
+        self.releases = true;
+        let key = key.to_ascii_lowercase();
+        let mut events = Vec::new();
+        if self.held.remove(&key).is_some() {
+            events.extend(button(key).map(ActionEvent::Released));
+        }
+        events.extend(self.tick(time));
+        events
+    }
+
+    /// Lets go of keys not seen for [`KEY_HOLD`] and ramps each axis output towards
+    /// what the held keys ask for, reporting what changed.
+    pub fn tick(&mut self, time: SystemTime) -> Vec<ActionEvent> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        if !self.releases {
+            let expired: Vec<char> = self.held.iter()
+                .filter(|(_, seen)| time.duration_since(**seen).unwrap_or_default() > KEY_HOLD)
+                .map(|(key, _)| *key)
+                .collect();
+            for key in expired {
+                self.held.remove(&key);
+                events.extend(button(key).map(ActionEvent::Released));
+            }
+        }
+        let elapsed = self.last_step.and_then(|last| time.duration_since(last).ok()).unwrap_or_default();
+        self.last_step = Some(time);
+        let step = self.ramp * elapsed.as_secs_f32();
+        for action in Action::iter().filter(Action::is_axis) {
+            let target: f32 = AXIS_KEYS.iter()
+                .filter(|(key, axis, _)| *axis == action && self.held.contains_key(key))
+                .map(|(.., direction)| direction)
+                .sum();
+            let output = self.outputs.entry(action).or_default();
+            let next = *output + (target - *output).clamp(-step, step);
+            if next != *output {
+                *output = next;
+                events.push(ActionEvent::Axis(action, next));
+            }
+        }
+        events
+    }
+
+    pub fn output(&self, action: Action) -> f32 {
+        self.outputs.get(&action).copied().unwrap_or_default()
+    }
+}
+
+fn is_held_key(key: char) -> bool {
+    AXIS_KEYS.iter().any(|(axis_key, ..)| *axis_key == key) || button(key).is_some()
+}
+
+fn button(key: char) -> Option<Action> {
+    BUTTON_KEYS.iter().find(|(button_key, _)| *button_key == key).map(|(_, action)| *action)
+}
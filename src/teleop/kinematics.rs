@@ -0,0 +1,116 @@
+use strum_macros::{Display, EnumIter, EnumString};
+
+use crate::protocol::DriveCommand;
+
+/// How conditioned stick input becomes wheel commands, picked to suit the chassis.
+#[derive(Debug, Display, Clone, Copy, Default, PartialEq, Eq, EnumIter, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum DriveMode {
+    /// Each stick's Y drives one side.
+    Tank,
+    /// Throttle plus turn, each side getting their sum or difference.
+    #[default]
+    Arcade,
+    /// Turn sets path curvature rather than turn rate, unless quick turn is held.
+    Curvature,
+    /// Strafe, throttle and turn on four independently driven omni or mecanum wheels.
+    Mecanum,
+}
+
+/// Conditioned drive axes, each in `-1.0..=1.0`: forward, right and clockwise are positive.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DriveAxes {
+    /// Strafe.
+    pub x: f32,
+    /// Throttle, and the left side in tank mode.
+    pub y: f32,
+    pub turn: f32,
+    /// The right side in tank mode.
+    pub right_y: f32,
+    /// Curvature drive turns on the spot while held.
+    pub quick_turn: bool,
+}
+
+/// Wheel outputs in [`DriveCommand::wheels`] order.
+pub type Wheels = [f32; 4];
+
+fn sides(left: f32, right: f32) -> Wheels {
+    let mut wheels = [0.0; 4];
+    wheels[DriveCommand::FRONT_LEFT] = left;
+    wheels[DriveCommand::REAR_LEFT] = left;
+    wheels[DriveCommand::FRONT_RIGHT] = right;
+    wheels[DriveCommand::REAR_RIGHT] = right;
+    normalise(wheels)
+}
+
+/// Scales all wheels down together when any is past full, keeping their ratios.
+fn normalise(wheels: Wheels) -> Wheels {
+    let max = wheels.iter().fold(0.0f32, |max, wheel| max.max(wheel.abs()));
+    if max > 1.0 { wheels.map(|wheel| wheel / max) } else { wheels }
+}
+
+pub fn tank(left: f32, right: f32) -> Wheels {
+    sides(left, right)
+}
+
+pub fn arcade(throttle: f32, turn: f32) -> Wheels {
+    sides(throttle + turn, throttle - turn)
+}
+
+/// "Cheesy" drive: turn rate scales with speed so the stick sets the arc, and
+/// `quick_turn` falls back to arcade for turning on the spot.
+pub fn curvature(throttle: f32, turn: f32, quick_turn: bool) -> Wheels {
+    if quick_turn {
+        return arcade(throttle, turn);
+    }
+    let rotation = throttle.abs() * turn;
+    sides(throttle + rotation, throttle - rotation)
+}
+
+/// Holonomic mix; with a heading, `x` and `y` are taken relative to the field rather
+/// than the robot. `heading_deg` is clockwise from where the robot faced at start.
+pub fn mecanum(x: f32, y: f32, turn: f32, heading_deg: Option<f32>) -> Wheels {
+    let (x, y) = match heading_deg {
+        Some(heading) => {
+            let (sin, cos) = heading.to_radians().sin_cos();
+            (x * cos - y * sin, x * sin + y * cos)
+        },
+        None => (x, y),
+    };
+    let mut wheels = [0.0; 4];
+    wheels[DriveCommand::FRONT_LEFT] = y + x + turn;
+    wheels[DriveCommand::FRONT_RIGHT] = y - x - turn;
+    wheels[DriveCommand::REAR_LEFT] = y - x + turn;
+    wheels[DriveCommand::REAR_RIGHT] = y + x - turn;
+    normalise(wheels)
+}
+
+/// The selected mixer and its options.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Kinematics {
+    pub mode: DriveMode,
+    /// Mecanum strafe and throttle follow the field, using the robot's reported heading.
+    pub field_oriented: bool,
+}
+
+impl Kinematics {
+    /// Wheel outputs for `axes`; field-oriented mecanum falls back to robot-oriented
+    /// while no heading is known.
+    pub fn mix(&self, axes: &DriveAxes, heading_deg: Option<f32>) -> Wheels {
+        match self.mode {
+            DriveMode::Tank => tank(axes.y, axes.right_y),
+            DriveMode::Arcade => arcade(axes.y, axes.turn),
+            DriveMode::Curvature => curvature(axes.y, axes.turn, axes.quick_turn),
+            DriveMode::Mecanum => mecanum(axes.x, axes.y, axes.turn, heading_deg.filter(|_| self.field_oriented)),
+        }
+    }
+
+    /// How the mode reads on the controller page.
+    pub fn describe(&self) -> String {
+        if self.mode == DriveMode::Mecanum && self.field_oriented {
+            format!("{} (field oriented)", self.mode)
+        } else {
+            self.mode.to_string()
+        }
+    }
+}
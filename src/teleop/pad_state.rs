@@ -0,0 +1,107 @@
+use std::{collections::{HashMap, VecDeque}, time::{Duration, SystemTime}};
+
+use gilrs::{Axis, Button, EventType as GamepadEventType};
+
+/// Span the event rate is averaged over.
+pub const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// Analog button value above which the button reads as pressed.
+const PRESSED: f32 = 0.5;
+
+/// Every axis and button of the followed pad as its event stream left them, so
+/// simultaneous inputs can be seen together.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PadState {
+    axes: HashMap<Axis, f32>,
+    /// Button values, `0.0` to `1.0`; analog triggers report in between.
+    buttons: HashMap<Button, f32>,
+    connected: bool,
+    /// When recent events arrived, oldest first, for the event rate.
+    recent: VecDeque<SystemTime>,
+    last_event: Option<SystemTime>,
+}
+
+impl PadState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, event: &GamepadEventType, time: SystemTime) {
+        match *event {
+            GamepadEventType::AxisChanged(axis, value, _) => self.set_axis(axis, value, time),
+            GamepadEventType::ButtonChanged(button, value, _) => self.set_button(button, value, time),
+            // Analog buttons also report their real value through ButtonChanged, which is kept.
+            GamepadEventType::ButtonPressed(button, _) => {
+                let value = if self.is_pressed(button) { self.button(button) } else { 1.0 };
+                self.set_button(button, value, time);
+            },
+            GamepadEventType::ButtonReleased(button, _) => {
+                let value = if self.is_pressed(button) { 0.0 } else { self.button(button) };
+                self.set_button(button, value, time);
+            },
+            GamepadEventType::Connected => {
+                self.connected = true;
+                self.record(time);
+            },
+            GamepadEventType::Disconnected => {
+                // A pad that is gone has nothing held.
+                self.axes.clear();
+                self.buttons.clear();
+                self.connected = false;
+                self.record(time);
+            },
+            _ => (),
+        }
+    }
+
+    pub fn set_axis(&mut self, axis: Axis, value: f32, time: SystemTime) {
+        self.axes.insert(axis, value);
+        self.connected = true;
+        self.record(time);
+    }
+
+    pub fn set_button(&mut self, button: Button, value: f32, time: SystemTime) {
+        self.buttons.insert(button, value);
+        self.connected = true;
+        self.record(time);
+    }
+
+    fn record(&mut self, time: SystemTime) {
+        self.last_event = Some(time);
+        self.recent.push_back(time);
+        self.expire(time);
+    }
+
+    fn expire(&mut self, now: SystemTime) {
+        while self.recent.front().is_some_and(|first| now.duration_since(*first).unwrap_or_default() > RATE_WINDOW) {
+            self.recent.pop_front();
+        }
+    }
+
+    pub fn axis(&self, axis: Axis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or_default()
+    }
+
+    pub fn button(&self, button: Button) -> f32 {
+        self.buttons.get(&button).copied().unwrap_or_default()
+    }
+
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.button(button) > PRESSED
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Events per second over the last [`RATE_WINDOW`] before `now`.
+    pub fn event_rate(&self, now: SystemTime) -> f32 {
+        let count = self.recent.iter()
+            .filter(|time| now.duration_since(**time).unwrap_or_default() <= RATE_WINDOW)
+            .count();
+        count as f32 / RATE_WINDOW.as_secs_f32()
+    }
+
+    pub fn since_last_event(&self, now: SystemTime) -> Option<Duration> {
+        self.last_event.map(|last| now.duration_since(last).unwrap_or_default())
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::SystemTime;
+
+use gilrs::{Axis, Button};
+use strum::IntoEnumIterator;
+
+use nightmare_gs::{protocol::DriveCommand, teleop::{Action, ActionEvent, ActionMapper, BindingError, Bindings, DriveInput, Input, Profile, INTAKE_BIT}};
+
+const PAD: &str = "030000005e0400008e02000014010000";
+
+fn axis(mapper: &mut ActionMapper, axis: Axis, value: f32) -> Vec<ActionEvent> {
+    mapper.map_input(Input::Axis(axis), value, SystemTime::UNIX_EPOCH)
+}
+
+fn press(mapper: &mut ActionMapper, button: Button) -> Vec<ActionEvent> {
+    mapper.map_input(Input::Button(button), 1.0, SystemTime::UNIX_EPOCH)
+}
+
+#[test]
+fn profiles_round_trip_through_text() {
+    let mut bindings = Bindings::new();
+    bindings.rebind(Some(PAD), Action::ArmLift, Input::Axis(Axis::LeftZ));
+    bindings.rebind(Some(PAD), Action::Estop, Input::Button(Button::Mode));
+
+    let parsed = Bindings::parse(&bindings.to_text()).unwrap();
+    assert_eq!(parsed, bindings);
+    assert_eq!(parsed.profile(PAD).input(Action::ArmLift), Some(Input::Axis(Axis::LeftZ)));
+    // Everything not rebound is copied from the default.
+    assert_eq!(parsed.profile(PAD).input(Action::DriveX), Some(Input::Axis(Axis::LeftStickX)));
+    assert_eq!(parsed.profile("another pad"), &Profile::default());
+}
+
+#[test]
+fn bad_lines_are_reported_with_their_number() {
+    let text = "# pads\n[default]\ndrive_x = axis LeftStickX\nfly = button South\n";
+    let error = Bindings::parse(text).unwrap_err();
+    assert!(matches!(error, BindingError::Parse { line: 4, .. }), "{}", error);
+
+    let error = Bindings::parse("[default]\nestop = button Jump\n").unwrap_err();
+    assert_eq!(error.to_string(), "Bad binding on line 2: unknown button 'Jump'");
+
+    assert!(Bindings::parse("drive_x = axis LeftStickX\n").is_err());
+    assert!(Bindings::parse("[not-a-uuid]\n").is_err());
+}
+
+#[test]
+fn axes_and_buttons_map_to_actions() {
+    let mut profile = Profile::default();
+    profile.bind(Action::IntakeToggle, Input::Axis(Axis::RightZ));
+    profile.bind(Action::ArmLift, Input::Button(Button::North));
+    let mut mapper = ActionMapper::new(profile);
+    let mut drive = DriveInput::new();
+
+    assert_eq!(axis(&mut mapper, Axis::LeftStickY, 0.25), [ActionEvent::Axis(Action::DriveY, 0.25)]);
+    assert_eq!(press(&mut mapper, Button::North), [ActionEvent::Axis(Action::ArmLift, 1.0)]);
+    assert!(press(&mut mapper, Button::South).is_empty());
+
+    // A trigger bound to a button action presses once on the way up and releases on the way down.
+    assert_eq!(axis(&mut mapper, Axis::RightZ, 0.8), [ActionEvent::Pressed(Action::IntakeToggle)]);
+    assert!(axis(&mut mapper, Axis::RightZ, 0.9).is_empty());
+    assert_eq!(axis(&mut mapper, Axis::RightZ, 0.1), [ActionEvent::Released(Action::IntakeToggle)]);
+
+    assert!(drive.update(&ActionEvent::Pressed(Action::IntakeToggle)));
+    assert!(!drive.update(&ActionEvent::Released(Action::IntakeToggle)));
+    assert_eq!(drive.command().buttons, INTAKE_BIT);
+    assert!(drive.update(&ActionEvent::Axis(Action::DriveY, 0.5)));
+    assert!(drive.reset());
+    assert_eq!(drive.command(), DriveCommand::neutral());
+}
+
+#[test]
+fn no_input_drives_two_axis_actions() {
+    let profile = Profile::default();
+    for action in Action::iter().filter(Action::is_axis) {
+        let input = profile.input(action).unwrap();
+        let sharing = Action::iter()
+            .filter(|other| other.is_axis() && profile.input(*other).is_some_and(|bound| bound.overlaps(&input)))
+            .count();
+        assert_eq!(sharing, 1, "{} shares {}", action, input);
+    }
+
+    let error = Bindings::parse("[default]\narm_lift = axis RightStickY\ntank_right = axis RightStickY\n").unwrap_err();
+    assert_eq!(error.to_string(), "Bad binding on line 3: axis RightStickY already drives arm_lift");
+    // A button action may still share an axis action's input.
+    assert!(Bindings::parse("[default]\narm_lift = axis RightZ\nquick_turn = axis RightZ\n").is_ok());
+
+    // Capturing an input another axis action uses moves it over.
+    let mut bindings = Bindings::new();
+    assert_eq!(bindings.rebind(Some(PAD), Action::TankRight, Input::Button(Button::DPadUp)), Some(Action::ArmLift));
+    assert_eq!(bindings.profile(PAD).input(Action::ArmLift), None);
+    assert_eq!(bindings.rebind(Some(PAD), Action::IntakeToggle, Input::Axis(Axis::LeftStickX)), None);
+    assert_eq!(bindings.profile(PAD).input(Action::DriveX), Some(Input::Axis(Axis::LeftStickX)));
+}
+
+#[test]
+fn default_arm_follows_the_dpad_buttons() {
+    // gilrs turns d-pad hat movement into DPadUp/DPadDown presses, so those are what arrive.
+    let mut mapper = ActionMapper::new(Profile::default());
+    let button = |mapper: &mut ActionMapper, button, value| mapper.map_input(Input::Button(button), value, SystemTime::UNIX_EPOCH);
+
+    assert_eq!(button(&mut mapper, Button::DPadUp, 1.0), [ActionEvent::Axis(Action::ArmLift, 1.0)]);
+    assert_eq!(button(&mut mapper, Button::DPadUp, 0.0), [ActionEvent::Axis(Action::ArmLift, 0.0)]);
+    assert_eq!(button(&mut mapper, Button::DPadDown, 1.0), [ActionEvent::Axis(Action::ArmLift, -1.0)]);
+    // Both held cancel out.
+    assert_eq!(button(&mut mapper, Button::DPadUp, 1.0), [ActionEvent::Axis(Action::ArmLift, 0.0)]);
+
+    let input: Input = "buttons DPadUp DPadDown".parse().unwrap();
+    assert_eq!(Profile::default().input(Action::ArmLift), Some(input));
+    assert_eq!(input.to_string(), "buttons DPadUp DPadDown");
+    assert!("buttons DPadUp DPadUp".parse::<Input>().is_err());
+    assert!("buttons DPadUp".parse::<Input>().is_err());
+}
@@ -0,0 +1,38 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use nightmare_gs::tasks::ClockSync;
+
+/// A robot clock that booted 5 s after the epoch and runs 100 ppm fast.
+fn robot_clock(ground_us: u64) -> u64 {
+    (ground_us as f64 * (1.0 + 100e-6)) as u64 - 5_000_000
+}
+
+#[test]
+fn offset_and_drift_are_recovered() {
+    let mut sync = ClockSync::new();
+    for exchange in 0..20u64 {
+        let t1 = 10_000_000 + exchange * 1_000_000;
+        // Every few exchanges sit in a queue; those must not skew the fit.
+        let delay = if exchange % 4 == 0 { 40_000 } else { 2_000 };
+        let t2 = robot_clock(t1 + delay / 2);
+        let t3 = t2 + 100;
+        let t4 = t1 + delay + 100;
+        sync.add_exchange(t1, t2, t3, t4);
+    }
+    let estimate = sync.estimate().unwrap();
+    assert!((estimate.drift_ppm() - 100.0).abs() < 1.0, "drift {}", estimate.drift_ppm());
+    assert_eq!(estimate.delay, Duration::from_micros(2_000));
+
+    let ground_us = 40_000_000;
+    let mapped = estimate.to_ground(robot_clock(ground_us));
+    let error = mapped.duration_since(UNIX_EPOCH).unwrap().as_micros() as i64 - ground_us as i64;
+    assert!(error.abs() < 50, "mapped {} us off", error);
+}
+
+#[test]
+fn no_estimate_until_an_exchange() {
+    let mut sync = ClockSync::new();
+    assert!(sync.estimate().is_none());
+    // A reply claiming to have taken longer than the round trip is discarded.
+    assert!(sync.add_exchange(1_000, 5_000, 9_000, 2_000).is_none());
+}
@@ -0,0 +1,111 @@
+use std::time::{Duration, SystemTime};
+
+use gilrs::{Axis, EventType as GamepadEventType};
+
+use nightmare_gs::teleop::{Action, ActionEvent, ActionMapper, AxisConditioning, AxisState, Bindings, Curve, Input, Profile, Stage};
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn static_stages_shape_the_value() {
+    let mut conditioning = AxisConditioning { deadzone: 0.1, ..AxisConditioning::default() };
+    assert_eq!(conditioning.shape(0.05, 0.05), 0.0);
+    // Output restarts from zero at the deadzone edge and still reaches full scale.
+    assert!(close(conditioning.shape(0.55, 0.55), 0.5));
+    assert!(close(conditioning.shape(-1.0, 1.0), -1.0));
+
+    conditioning.curve = Curve::Cubic(1.0);
+    conditioning.invert = true;
+    conditioning.scale = 0.5;
+    assert!(close(conditioning.shape(0.55, 0.55), -0.0625));
+
+    let radial = AxisConditioning { radial: 0.2, ..AxisConditioning::default() };
+    // Inside the radial deadzone the axis reads zero even if it alone is past it...
+    assert_eq!(radial.shape(0.15, 0.18), 0.0);
+    // ...and outside it is scaled along the stick's direction.
+    assert!(close(radial.shape(0.6, 1.0), 0.6));
+
+    assert!(close(Curve::Expo(3.0).apply(1.0), 1.0));
+    assert!(Curve::Expo(3.0).apply(0.5) < 0.5);
+    assert!(close(Curve::Expo(3.0).apply(-0.5), -Curve::Expo(3.0).apply(0.5)));
+}
+
+#[test]
+fn stages_are_set_from_text_and_checked() {
+    let mut conditioning = AxisConditioning::default();
+    conditioning.set(Stage::Curve, "expo 2.5").unwrap();
+    assert_eq!(conditioning.curve, Curve::Expo(2.5));
+    conditioning.set(Stage::Invert, "true").unwrap();
+    assert!(conditioning.invert);
+    assert!(conditioning.set(Stage::Deadzone, "1").is_err());
+    assert!(conditioning.set(Stage::Slew, "-2").is_err());
+    assert!(conditioning.set(Stage::Curve, "cubic 3").is_err());
+    assert!(conditioning.set(Stage::Scale, "lots").is_err());
+    for amount in ["NaN", "inf", "-inf"] {
+        assert!(conditioning.set(Stage::Curve, &format!("expo {}", amount)).is_err());
+    }
+    assert_eq!(conditioning.curve, Curve::Expo(2.5));
+}
+
+#[test]
+fn slew_limit_spreads_a_step_over_time() {
+    let mut profile = Profile::default();
+    profile.set_conditioning(Action::DriveY, AxisConditioning { slew: 2.0, ..AxisConditioning::default() });
+    let mut mapper = ActionMapper::new(profile);
+    let start = SystemTime::UNIX_EPOCH;
+
+    assert!(mapper.map_input(Input::Axis(Axis::LeftStickY), 1.0, start).is_empty());
+    assert_eq!(mapper.state(Action::DriveY).target, 1.0);
+    assert_eq!(mapper.tick(start + Duration::from_millis(250)), [ActionEvent::Axis(Action::DriveY, 0.5)]);
+    assert!(!mapper.state(Action::DriveY).is_settled());
+    assert_eq!(mapper.tick(start + Duration::from_secs(1)), [ActionEvent::Axis(Action::DriveY, 1.0)]);
+    assert!(mapper.state(Action::DriveY).is_settled());
+    assert!(mapper.tick(start + Duration::from_secs(2)).is_empty());
+}
+
+#[test]
+fn low_pass_filter_approaches_the_target() {
+    let mut profile = Profile::default();
+    profile.set_conditioning(Action::DriveX, AxisConditioning { filter: 0.1, ..AxisConditioning::default() });
+    let mut mapper = ActionMapper::new(profile);
+    let start = SystemTime::UNIX_EPOCH;
+
+    mapper.map_input(Input::Axis(Axis::LeftStickX), 1.0, start);
+    mapper.tick(start + Duration::from_millis(100));
+    assert!(close(mapper.state(Action::DriveX).output, 1.0 - (-1.0f32).exp()));
+    mapper.tick(start + Duration::from_secs(2));
+    assert_eq!(mapper.state(Action::DriveX).output, 1.0);
+}
+
+#[test]
+fn conditioning_is_saved_with_the_bindings() {
+    let mut bindings = Bindings::new();
+    let conditioning = AxisConditioning { deadzone: 0.08, curve: Curve::Cubic(0.4), slew: 3.0, ..AxisConditioning::default() };
+    bindings.profile_mut(None).set_conditioning(Action::DriveTurn, conditioning);
+
+    let text = bindings.to_text();
+    assert!(text.contains("drive_turn.deadzone = 0.08\n"), "{}", text);
+    assert!(!text.contains("drive_turn.scale"), "{}", text);
+    let parsed = Bindings::parse(&text).unwrap();
+    assert_eq!(parsed.profile("any").conditioning(Action::DriveTurn), conditioning);
+
+    assert!(Bindings::parse("[default]\nestop.deadzone = 0.1\n").is_err());
+    assert!(Bindings::parse("[default]\ndrive_x.wobble = 1\n").is_err());
+}
+
+#[test]
+fn unplugged_pad_stops_driving_at_once() {
+    let mut profile = Profile::default();
+    profile.set_conditioning(Action::DriveY, AxisConditioning { slew: 2.0, ..AxisConditioning::default() });
+    let mut mapper = ActionMapper::new(profile);
+    let start = SystemTime::UNIX_EPOCH;
+
+    mapper.map_input(Input::Axis(Axis::LeftStickY), 1.0, start);
+    assert_eq!(mapper.tick(start + Duration::from_millis(250)), [ActionEvent::Axis(Action::DriveY, 0.5)]);
+    assert!(mapper.map(&GamepadEventType::Disconnected, start + Duration::from_millis(300)).is_empty());
+    assert_eq!(mapper.state(Action::DriveY), AxisState::default());
+    assert!(mapper.tick(start + Duration::from_secs(1)).is_empty());
+    assert!(mapper.tick(start + Duration::from_secs(2)).is_empty());
+}
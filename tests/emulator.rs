@@ -0,0 +1,520 @@
+use std::time::{Duration, SystemTime};
+
+use nightmare_gs::{
+    emulator::{EmulatorConfig, FaultConfig, RobotEmulator, NACK_WRONG_BASE},
+    event::Event,
+    protocol::{crc32, DriveCommand, FaultFlags, LogLevel, ParamValue},
+    tasks::{probe, transport_for, CommandPhase, DriverError, DriverEvent, DriverRegistry, DriverState, DriverTask, FirmwareImage, LineSettings, LinkStatus, NetProtocol, ParamEvent, ParamRequest, RawDirection, UploadStage},
+};
+use tokio::{sync::mpsc, time};
+
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn start_driver(emulator: &RobotEmulator) -> (DriverTask, mpsc::UnboundedReceiver<Event>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let mut driver_task = DriverTask::new("robot".to_string());
+    driver_task.set_sender(sender);
+    driver_task.set_port(emulator.path().to_string()).expect("PTY path should be accepted");
+    driver_task.start_driver().expect("driver should start");
+    (driver_task, receiver)
+}
+
+/// Waits for the first driver event matching `predicate`, skipping everything else.
+async fn wait_for(receiver: &mut mpsc::UnboundedReceiver<Event>, predicate: impl Fn(&DriverEvent) -> bool) -> DriverEvent {
+    time::timeout(EVENT_TIMEOUT, async {
+        loop {
+            match receiver.recv().await {
+                Some(Event::Driver(_, event)) if predicate(&event) => return event,
+                Some(_) => (),
+                None => panic!("event channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for driver event")
+}
+
+/// Polls until `condition` holds, panicking with `what` if it never does.
+async fn wait_until(what: &str, condition: impl Fn() -> bool) {
+    time::timeout(EVENT_TIMEOUT, async {
+        while !condition() {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("timed out waiting for {}", what))
+}
+
+/// Like [`wait_for_state`], for the driver named `driver` among several.
+async fn wait_for_driver_state(receiver: &mut mpsc::UnboundedReceiver<Event>, driver: &str, state: DriverState) {
+    time::timeout(EVENT_TIMEOUT, async {
+        while !matches!(receiver.recv().await,
+            Some(Event::Driver(name, DriverEvent::StateReport(reported))) if name == driver && reported == state) {}
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{} never reported {}", driver, state))
+}
+
+async fn wait_for_state(receiver: &mut mpsc::UnboundedReceiver<Event>, state: DriverState) {
+    wait_for(receiver, |event| matches!(event, DriverEvent::StateReport(reported) if *reported == state)).await;
+}
+
+#[tokio::test]
+async fn handshake_and_state_changes() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+
+    let identity = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Identity(_))).await;
+    let DriverEvent::Identity(identity) = identity else { unreachable!() };
+    assert_eq!(identity.name, "nightmare-emulator");
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    driver_task.request_state(DriverState::Disabled).unwrap();
+    wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::Command(status) if status.phase == CommandPhase::Acked)).await;
+    wait_for_state(&mut receiver, DriverState::Disabled).await;
+
+    driver_task.request_state(DriverState::Enabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Enabled).await;
+    assert_eq!(emulator.snapshot().state, DriverState::Enabled);
+
+    let command = DriveCommand { wheels: [0.5; 4], arm: -0.25, ..DriveCommand::neutral() };
+    driver_task.set_drive(command);
+    time::timeout(EVENT_TIMEOUT, async {
+        while !emulator.snapshot().last_drive.is_some_and(|drive| (drive.wheels[0] - 0.5).abs() < 0.001 && (drive.arm + 0.25).abs() < 0.001) {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("robot never saw the drive command");
+}
+
+#[tokio::test]
+async fn estop_latches_until_reset() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    driver_task.request_state(DriverState::EStopped).unwrap();
+    wait_for_state(&mut receiver, DriverState::EStopped).await;
+
+    driver_task.request_state(DriverState::Disabled).unwrap();
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::StateChangeError(_))).await;
+
+    driver_task.reset().unwrap();
+    wait_for_state(&mut receiver, DriverState::Disabled).await;
+}
+
+#[tokio::test]
+async fn enabling_straight_from_connected_is_refused() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    driver_task.request_state(DriverState::Enabled).unwrap();
+    let refusal = wait_for(&mut receiver, |event| matches!(event, DriverEvent::StateChangeError(_))).await;
+    let DriverEvent::StateChangeError(reason) = refusal else { unreachable!() };
+    assert!(reason.contains("disabled before enabling"), "{}", reason);
+    assert_ne!(emulator.snapshot().state, DriverState::Enabled);
+}
+
+#[tokio::test]
+async fn corrupt_frames_are_reported_not_fatal() {
+    let config = EmulatorConfig {
+        faults: FaultConfig { corrupt_frame_rate: 1.0, ..FaultConfig::default() },
+        ..EmulatorConfig::default()
+    };
+    let emulator = RobotEmulator::spawn(config).unwrap();
+    let (_driver_task, mut receiver) = start_driver(&emulator);
+
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::ProtocolError(_))).await;
+    wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::LinkStats(stats) if stats.frames_corrupt > 0)).await;
+
+    emulator.set_faults(FaultConfig::default());
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+}
+
+#[tokio::test]
+async fn registry_runs_drivers_side_by_side() {
+    let main = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let arm = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut registry = DriverRegistry::new();
+    registry.set_sender(sender);
+    registry.start(main.path().to_string()).unwrap();
+    registry.start(arm.path().to_string()).unwrap();
+    let main_name = main.path().rsplit('/').next().unwrap().to_string();
+    assert_eq!(registry.active_name(), Some(main_name.as_str()));
+
+    let mut connected = Vec::new();
+    time::timeout(EVENT_TIMEOUT, async {
+        while connected.len() < 2 {
+            if let Some(Event::Driver(name, DriverEvent::StateReport(DriverState::Connected))) = receiver.recv().await {
+                if !connected.contains(&name) {
+                    connected.push(name);
+                }
+            }
+        }
+    })
+    .await
+    .expect("both drivers should connect");
+
+    registry.estop_all().unwrap();
+    time::timeout(EVENT_TIMEOUT, async {
+        while main.snapshot().state != DriverState::EStopped || arm.snapshot().state != DriverState::EStopped {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("e-stop should reach both robots");
+
+    registry.stop(arm.path()).unwrap();
+    assert!(registry.request_state(DriverState::Disabled).is_ok());
+}
+
+#[tokio::test]
+async fn raw_bytes_are_reported_alongside_frames() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::Raw(chunk) if chunk.direction == RawDirection::Rx)).await;
+
+    driver_task.send_raw(b"AT\r\n".to_vec()).unwrap();
+    wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::Raw(chunk) if chunk.direction == RawDirection::Tx && chunk.bytes == b"AT\r\n")).await;
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+}
+
+#[tokio::test]
+async fn firmware_upload_verifies_and_reboots() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let image = FirmwareImage::from_bytes("test.bin".to_string(), data.clone()).unwrap();
+    driver_task.upload_firmware(image).unwrap();
+    let done = wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::UploadProgress(progress) if progress.is_finished())).await;
+    let DriverEvent::UploadProgress(progress) = done else { unreachable!() };
+    assert_eq!(progress.stage, UploadStage::Done);
+    assert_eq!(progress.sent, data.len());
+
+    time::timeout(EVENT_TIMEOUT, async {
+        while emulator.snapshot().reboots == 0 {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("robot never rebooted");
+    assert_eq!(emulator.snapshot().firmware, Some((data.len(), crc32(&data))));
+}
+
+#[tokio::test]
+async fn firmware_linked_elsewhere_is_refused_before_flashing() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    let image = FirmwareImage { name: "boot.hex".to_string(), base_address: Some(0x0800_0000), data: vec![0xAA; 64] };
+    driver_task.upload_firmware(image).unwrap();
+    let failed = wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::UploadProgress(progress) if progress.is_finished())).await;
+    let DriverEvent::UploadProgress(progress) = failed else { unreachable!() };
+    assert_eq!(progress.stage, UploadStage::Failed(format!("robot won't flash an image linked at 0x08000000 (code {})", NACK_WRONG_BASE)));
+    assert_eq!(progress.sent, 0);
+
+    let base = EmulatorConfig::default().app_base;
+    let data = vec![0x55; 300];
+    let image = FirmwareImage { name: "app.hex".to_string(), base_address: Some(base), data: data.clone() };
+    driver_task.upload_firmware(image).unwrap();
+    let done = wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::UploadProgress(progress) if progress.is_finished())).await;
+    assert!(matches!(done, DriverEvent::UploadProgress(progress) if progress.stage == UploadStage::Done));
+    wait_until("robot to reboot", || emulator.snapshot().reboots == 1).await;
+    assert_eq!(emulator.snapshot().firmware, Some((data.len(), crc32(&data))));
+}
+
+#[tokio::test]
+async fn firmware_upload_refused_while_enabled() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+    driver_task.request_state(DriverState::Disabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Disabled).await;
+    driver_task.request_state(DriverState::Enabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Enabled).await;
+
+    let image = FirmwareImage::from_bytes("test.bin".to_string(), vec![0xAA; 64]).unwrap();
+    driver_task.upload_firmware(image).unwrap();
+    let failed = wait_for(&mut receiver, |event| matches!(event, DriverEvent::UploadProgress(_))).await;
+    assert!(matches!(failed, DriverEvent::UploadProgress(progress) if matches!(progress.stage, UploadStage::Failed(_))));
+    assert_eq!(emulator.snapshot().reboots, 0);
+}
+
+#[tokio::test]
+async fn parameters_are_listed_set_and_saved() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let expected = EmulatorConfig::default().params;
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    driver_task.request_param(ParamRequest::List).unwrap();
+    let mut listed = Vec::new();
+    while listed.len() < expected.len() {
+        let event = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Param(ParamEvent::Info { .. }))).await;
+        let DriverEvent::Param(ParamEvent::Info { count, info }) = event else { unreachable!() };
+        assert_eq!(count as usize, expected.len());
+        listed.push(info);
+    }
+    assert_eq!(listed, expected);
+
+    driver_task.request_param(ParamRequest::Set(0, ParamValue::Float(2.0))).unwrap();
+    let event = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Param(_))).await;
+    assert!(matches!(event, DriverEvent::Param(ParamEvent::Value { index: 0, value: ParamValue::Float(value) }) if value == 2.0));
+
+    driver_task.request_param(ParamRequest::Set(0, ParamValue::Float(50.0))).unwrap();
+    let event = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Param(_))).await;
+    assert!(matches!(event, DriverEvent::Param(ParamEvent::Failed(_))));
+
+    driver_task.request_param(ParamRequest::Save).unwrap();
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::Param(ParamEvent::Saved))).await;
+    let saved = emulator.snapshot().saved_params.expect("robot should have saved");
+    assert_eq!(saved[0], ParamValue::Float(2.0));
+}
+
+#[tokio::test]
+async fn telemetry_is_mapped_to_ground_time() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (_driver_task, mut receiver) = start_driver(&emulator);
+
+    let estimate = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Clock(estimate) if estimate.samples >= 2)).await;
+    let DriverEvent::Clock(estimate) = estimate else { unreachable!() };
+    assert!(estimate.delay < Duration::from_millis(100), "round trip {:?}", estimate.delay);
+
+    let sample = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Telemetry(_))).await;
+    let DriverEvent::Telemetry(sample) = sample else { unreachable!() };
+    let ground_time = sample.ground_time.expect("telemetry after sync should have a ground time");
+    let age = SystemTime::now().duration_since(ground_time).unwrap_or_else(|e| e.duration());
+    assert!(age < Duration::from_millis(100), "telemetry mapped {:?} away from receipt", age);
+}
+
+#[tokio::test]
+async fn robot_log_lines_are_forwarded() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    driver_task.request_state(DriverState::Disabled).unwrap();
+    let entry = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Log(_))).await;
+    let DriverEvent::Log(entry) = entry else { unreachable!() };
+    assert_eq!(entry.level, LogLevel::Info);
+    assert_eq!(entry.tag, "state");
+    assert!(entry.text.ends_with(&DriverState::Disabled.to_string()), "{}", entry.text);
+}
+
+/// Same handshake and state change as over the PTY, against a localhost socket.
+async fn runs_over_network(protocol: NetProtocol) {
+    let emulator = RobotEmulator::spawn_network(protocol, EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    assert_eq!(driver_task.port().unwrap().name(), emulator.path());
+
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::Identity(_))).await;
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+    driver_task.request_state(DriverState::Disabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Disabled).await;
+    assert_eq!(emulator.snapshot().state, DriverState::Disabled);
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::Telemetry(_))).await;
+}
+
+#[tokio::test]
+async fn driver_runs_over_tcp() {
+    runs_over_network(NetProtocol::Tcp).await;
+}
+
+#[tokio::test]
+async fn driver_runs_over_udp() {
+    runs_over_network(NetProtocol::Udp).await;
+}
+
+#[test]
+fn network_addresses_need_a_port() {
+    let mut driver_task = DriverTask::new("robot".to_string());
+    for endpoint in ["tcp://localhost", "udp://:5000", "tcp://localhost:http"] {
+        assert!(matches!(driver_task.set_port(endpoint.to_string()), Err(DriverError::InvalidAddress(_))), "{}", endpoint);
+    }
+    driver_task.set_port("udp://192.168.4.1:5000".to_string()).unwrap();
+}
+
+#[tokio::test]
+async fn probe_finds_robot_and_times_out_on_silence() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let transport = transport_for(emulator.path(), LineSettings::default()).unwrap();
+    let identity = probe(transport.as_ref(), Duration::from_secs(1)).await.unwrap();
+    assert_eq!(identity.name, "nightmare-emulator");
+    assert_eq!(identity.firmware, env!("CARGO_PKG_VERSION"));
+
+    emulator.set_faults(FaultConfig { silent: true, ..FaultConfig::default() });
+    let silent = probe(transport.as_ref(), Duration::from_millis(200)).await;
+    assert_eq!(silent, Err("no answer".to_string()));
+}
+
+#[tokio::test]
+async fn telemetry_follows_drive_and_reports_faults() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+    driver_task.request_state(DriverState::Disabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Disabled).await;
+    driver_task.request_state(DriverState::Enabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Enabled).await;
+    // Spinning on the spot: left side forward, right side back.
+    driver_task.set_drive(DriveCommand { wheels: [1.0, -1.0, 1.0, -1.0], ..DriveCommand::neutral() });
+
+    let moving = wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::Telemetry(sample) if sample.telemetry.motors.len() == 2 && sample.telemetry.motors[0].encoder_ticks > 200)).await;
+    let DriverEvent::Telemetry(moving) = moving else { unreachable!() };
+    assert!(moving.telemetry.motors[1].encoder_ticks < 0);
+    assert!(moving.telemetry.motors[0].current_a > 1.0);
+    assert!(moving.telemetry.heading_deg.is_some_and(|heading| heading > 0.0), "{:?}", moving.telemetry.heading_deg);
+    assert!(moving.telemetry.battery_v > 10.0 && moving.telemetry.battery_v < 12.6);
+
+    emulator.set_faults(FaultConfig { raised: FaultFlags(FaultFlags::OVERTEMPERATURE), ..FaultConfig::default() });
+    wait_for(&mut receiver, |event| matches!(event,
+        DriverEvent::Telemetry(sample) if sample.telemetry.faults.contains(FaultFlags::OVERTEMPERATURE))).await;
+}
+
+#[tokio::test]
+async fn console_bytes_are_refused_while_offline() {
+    // A port nothing listens on keeps the driver retrying.
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut driver_task = DriverTask::new("robot".to_string());
+    driver_task.set_sender(sender);
+    driver_task.set_port(format!("tcp://{}", closed)).unwrap();
+    driver_task.start_driver().unwrap();
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::Link(LinkStatus::Retrying { .. }))).await;
+
+    driver_task.send_raw(b"ping".to_vec()).unwrap();
+    let refusal = wait_for(&mut receiver, |event| matches!(event, DriverEvent::RawFailed(_) | DriverEvent::Raw(_))).await;
+    assert!(matches!(&refusal, DriverEvent::RawFailed(reason) if reason.contains("4 byte")), "{:?}", refusal);
+}
+
+#[tokio::test]
+async fn selecting_another_driver_stops_the_previous_robot() {
+    let main = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let arm = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut registry = DriverRegistry::new();
+    registry.set_sender(sender);
+    registry.start(main.path().to_string()).unwrap();
+    registry.start(arm.path().to_string()).unwrap();
+
+    let main_name = main.path().rsplit('/').next().unwrap().to_string();
+    wait_for_driver_state(&mut receiver, &main_name, DriverState::Connected).await;
+    registry.request_state(DriverState::Disabled).unwrap();
+    wait_for_driver_state(&mut receiver, &main_name, DriverState::Disabled).await;
+    registry.request_state(DriverState::Enabled).unwrap();
+    wait_for_driver_state(&mut receiver, &main_name, DriverState::Enabled).await;
+
+    let command = DriveCommand { wheels: [0.5; 4], ..DriveCommand::neutral() };
+    registry.set_drive(command);
+    wait_until("main robot to drive", || main.snapshot().last_drive.is_some_and(|drive| (drive.wheels[0] - 0.5).abs() < 0.001)).await;
+
+    registry.select(arm.path()).unwrap();
+    wait_until("main robot to stop", || main.snapshot().last_drive == Some(DriveCommand::neutral())).await;
+    assert_eq!(main.snapshot().state, DriverState::Enabled);
+}
+
+#[tokio::test]
+async fn unanswered_commands_are_retried_then_reported() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    emulator.set_faults(FaultConfig { silent: true, ..FaultConfig::default() });
+    let started = time::Instant::now();
+    driver_task.request_state(DriverState::Disabled).unwrap();
+
+    // The default policy sends once and retries three times, 250 ms apart, on the same sequence number.
+    let mut attempts = Vec::new();
+    let failure = time::timeout(EVENT_TIMEOUT, async {
+        loop {
+            match receiver.recv().await {
+                Some(Event::Driver(_, DriverEvent::Command(status))) => match status.phase {
+                    CommandPhase::Pending => attempts.push((status.seq, status.attempt)),
+                    CommandPhase::Failed(reason) => break reason,
+                    CommandPhase::Acked => panic!("a silent robot acknowledged {}", status),
+                },
+                Some(_) => (),
+                None => panic!("event channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("command never gave up");
+    assert_eq!(failure, "no acknowledgement after 4 attempts");
+    assert_eq!(attempts.iter().map(|(_, attempt)| *attempt).collect::<Vec<_>>(), [1, 2, 3, 4]);
+    assert!(attempts.iter().all(|(seq, _)| *seq == attempts[0].0));
+    assert!(started.elapsed() >= Duration::from_millis(4 * 250));
+
+    let error = wait_for(&mut receiver, |event| matches!(event, DriverEvent::StateChangeError(_))).await;
+    let DriverEvent::StateChangeError(error) = error else { unreachable!() };
+    assert_eq!(error, "Change to Disabled failed: no acknowledgement after 4 attempts");
+}
+
+#[tokio::test]
+async fn drive_stays_neutral_until_enabled() {
+    let emulator = RobotEmulator::spawn(EmulatorConfig::default()).unwrap();
+    let (mut driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+    driver_task.request_state(DriverState::Disabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Disabled).await;
+
+    driver_task.set_drive(DriveCommand { wheels: [0.8; 4], arm: 0.5, buttons: 1 });
+    let seen = emulator.snapshot().drives_received;
+    // Ten drive ticks at the default 50 Hz, all of which must be neutral.
+    wait_until("drive ticks while disabled", || emulator.snapshot().drives_received >= seen + 10).await;
+    assert_eq!(emulator.snapshot().moving_drives_received, 0);
+    assert_eq!(emulator.snapshot().last_drive, Some(DriveCommand::neutral()));
+
+    driver_task.request_state(DriverState::Enabled).unwrap();
+    wait_for_state(&mut receiver, DriverState::Enabled).await;
+    wait_until("drive to reach the enabled robot", || emulator.snapshot().moving_drives_received > 0).await;
+}
+
+#[tokio::test]
+async fn open_failures_name_the_port() {
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let endpoint = format!("tcp://{}", closed);
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut driver_task = DriverTask::new("robot".to_string());
+    driver_task.set_sender(sender);
+    driver_task.set_port(endpoint.clone()).unwrap();
+    driver_task.start_driver().unwrap();
+
+    let error = wait_for(&mut receiver, |event| matches!(event, DriverEvent::Error(_))).await;
+    let DriverEvent::Error(error) = error else { unreachable!() };
+    assert!(matches!(&error, DriverError::OpenFailed { port, .. } if *port == endpoint), "{:?}", error);
+    assert!(error.to_string().starts_with(&format!("Couldn't open {}: ", endpoint)), "{}", error);
+}
+
+#[tokio::test]
+async fn driver_reconnects_when_the_robot_comes_back() {
+    let emulator = RobotEmulator::spawn_network(NetProtocol::Tcp, EmulatorConfig::default()).unwrap();
+    let endpoint = emulator.path().to_string();
+    let (_driver_task, mut receiver) = start_driver(&emulator);
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+
+    drop(emulator);
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::Link(LinkStatus::Lost(_)))).await;
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::Link(LinkStatus::Retrying { .. }))).await;
+
+    let address = endpoint.strip_prefix("tcp://").unwrap();
+    let emulator = RobotEmulator::spawn_network_at(NetProtocol::Tcp, address, EmulatorConfig::default()).unwrap();
+    wait_for_state(&mut receiver, DriverState::Connected).await;
+    // Stats start over with each connection, so a round trip means heartbeats are answered again.
+    wait_for(&mut receiver, |event| matches!(event, DriverEvent::LinkStats(stats) if stats.rtt.is_some())).await;
+    assert!(emulator.snapshot().frames_received > 0);
+}
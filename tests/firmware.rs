@@ -0,0 +1,21 @@
+use nightmare_gs::tasks::{FirmwareError, FirmwareImage};
+
+#[test]
+fn intel_hex_is_flattened_from_lowest_address() {
+    let text = "\
+:020000040800F2
+:0400000001020304F2
+:02000800AABB91
+:00000001FF
+";
+    let image = FirmwareImage::parse_hex("app.hex".to_string(), text).unwrap();
+    assert_eq!(image.base_address, Some(0x0800_0000));
+    assert_eq!(image.data, vec![1, 2, 3, 4, 0xFF, 0xFF, 0xFF, 0xFF, 0xAA, 0xBB]);
+}
+
+#[test]
+fn intel_hex_checksum_errors_name_the_line() {
+    let text = ":0400000001020304F2\n:0400040005060708FF\n";
+    let error = FirmwareImage::parse_hex("app.hex".to_string(), text).unwrap_err();
+    assert!(matches!(error, FirmwareError::BadHex { line: 2, .. }));
+}
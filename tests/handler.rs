@@ -0,0 +1,28 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+
+use nightmare_gs::{app::App, handler::handle_key_events};
+
+fn key(app: &mut App, code: KeyCode, kind: KeyEventKind) {
+    handle_key_events(KeyEvent { code, modifiers: KeyModifiers::NONE, kind, state: KeyEventState::NONE }, app).unwrap();
+}
+
+#[test]
+fn repeats_do_not_drive_the_menus() {
+    let mut app = App::new();
+    key(&mut app, KeyCode::Tab, KeyEventKind::Repeat);
+    assert!(!app.is_keyboard_driving());
+    key(&mut app, KeyCode::Char('q'), KeyEventKind::Repeat);
+    assert!(app.is_running());
+
+    key(&mut app, KeyCode::Tab, KeyEventKind::Press);
+    assert!(app.is_keyboard_driving());
+    // Leaving drive mode takes a fresh press too.
+    key(&mut app, KeyCode::Tab, KeyEventKind::Repeat);
+    key(&mut app, KeyCode::Tab, KeyEventKind::Release);
+    assert!(app.is_keyboard_driving());
+    key(&mut app, KeyCode::Esc, KeyEventKind::Press);
+    assert!(!app.is_keyboard_driving());
+
+    key(&mut app, KeyCode::Char('q'), KeyEventKind::Press);
+    assert!(!app.is_running());
+}
@@ -0,0 +1,124 @@
+use std::time::{Duration, SystemTime};
+
+use nightmare_gs::teleop::{Action, ActionEvent, DriveInput, KeyboardDrive, DEFAULT_RAMP, KEY_HOLD, RAMP_STEP};
+
+fn at(ms: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+}
+
+fn driving() -> KeyboardDrive {
+    let mut keyboard = KeyboardDrive::new();
+    assert!(keyboard.set_enabled(true).is_empty());
+    keyboard
+}
+
+#[test]
+fn keys_do_nothing_until_driving() {
+    let mut keyboard = KeyboardDrive::new();
+    assert!(!keyboard.is_enabled());
+    assert!(keyboard.press('w', at(0)).is_empty());
+    assert!(keyboard.tick(at(1000)).is_empty());
+    assert_eq!(keyboard.output(Action::DriveY), 0.0);
+}
+
+#[test]
+fn held_keys_ramp_towards_full_and_back() {
+    let mut keyboard = driving();
+    keyboard.tick(at(0));
+    keyboard.press('w', at(0));
+    // Auto-repeat keeps the key held; the output climbs at the ramp rate.
+    keyboard.press('w', at(100));
+    assert!((keyboard.output(Action::DriveY) - 0.1 * DEFAULT_RAMP).abs() < 1e-4);
+    keyboard.press('w', at(500));
+    keyboard.press('w', at(1000));
+    assert_eq!(keyboard.output(Action::DriveY), 1.0);
+    // Steering is independent of throttle.
+    keyboard.press('a', at(1000));
+    let events = keyboard.tick(at(1100));
+    assert!(events.contains(&ActionEvent::Axis(Action::DriveTurn, -0.1 * DEFAULT_RAMP)), "{:?}", events);
+
+    // Once repeats stop the keys expire and the outputs ramp back to zero.
+    keyboard.tick(at(1000 + KEY_HOLD.as_millis() as u64 + 1));
+    keyboard.tick(at(5000));
+    assert_eq!(keyboard.output(Action::DriveY), 0.0);
+    assert_eq!(keyboard.output(Action::DriveTurn), 0.0);
+}
+
+#[test]
+fn opposite_keys_cancel_and_releases_are_honoured() {
+    let mut keyboard = driving();
+    keyboard.tick(at(0));
+    keyboard.press('w', at(0));
+    keyboard.press('s', at(0));
+    keyboard.tick(at(300));
+    assert_eq!(keyboard.output(Action::DriveY), 0.0);
+
+    // A terminal that reports releases holds keys until they come up, however long.
+    keyboard.release('s', at(300));
+    keyboard.tick(at(300 + 10 * KEY_HOLD.as_millis() as u64));
+    assert_eq!(keyboard.output(Action::DriveY), 1.0);
+    keyboard.release('w', at(10_000));
+    keyboard.tick(at(20_000));
+    assert_eq!(keyboard.output(Action::DriveY), 0.0);
+}
+
+#[test]
+fn hotkeys_press_and_release_button_actions() {
+    let mut keyboard = driving();
+    assert_eq!(keyboard.press('i', at(0)), vec![ActionEvent::Pressed(Action::IntakeToggle)]);
+    // Repeats don't press again.
+    assert!(keyboard.press('I', at(50)).is_empty());
+    assert_eq!(keyboard.release('i', at(100)), vec![ActionEvent::Released(Action::IntakeToggle)]);
+    assert!(KeyboardDrive::is_drive_key('G'));
+    assert!(!KeyboardDrive::is_drive_key('x'));
+}
+
+#[test]
+fn ramp_is_adjustable_within_limits() {
+    let mut keyboard = driving();
+    keyboard.press(']', at(0));
+    assert_eq!(keyboard.ramp(), DEFAULT_RAMP + RAMP_STEP);
+    keyboard.press('[', at(0));
+    keyboard.press('[', at(0));
+    assert_eq!(keyboard.ramp(), DEFAULT_RAMP - RAMP_STEP);
+    assert!(keyboard.set_ramp(0.0).is_err());
+    assert!(keyboard.set_ramp(100.0).is_err());
+    keyboard.set_ramp(10.0).unwrap();
+    keyboard.tick(at(0));
+    keyboard.press('d', at(0));
+    keyboard.tick(at(50));
+    assert!((keyboard.output(Action::DriveTurn) - 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn leaving_drive_mode_stops_at_once_through_the_same_actions_as_a_pad() {
+    let mut keyboard = driving();
+    let mut drive = DriveInput::new();
+    keyboard.tick(at(0));
+    for event in keyboard.press('w', at(0)).into_iter().chain(keyboard.press('g', at(0))).chain(keyboard.tick(at(500))) {
+        drive.update(&event);
+    }
+    assert_eq!(drive.command().wheels, [1.0; 4]);
+
+    let events = keyboard.set_enabled(false);
+    assert!(events.contains(&ActionEvent::Axis(Action::DriveY, 0.0)));
+    assert!(events.contains(&ActionEvent::Released(Action::QuickTurn)));
+    for event in &events {
+        drive.update(event);
+    }
+    assert_eq!(drive.command().wheels, [0.0; 4]);
+    assert_eq!(keyboard.output(Action::DriveY), 0.0);
+}
+
+#[test]
+fn keys_are_held_until_released_where_the_terminal_reports_it() {
+    let mut keyboard = driving();
+    keyboard.set_reports_releases(true);
+    keyboard.press('w', at(0));
+    // No auto-repeat arrives, yet the key is still down well past the hold time.
+    keyboard.tick(at(KEY_HOLD.as_millis() as u64 * 3));
+    assert!(keyboard.output(Action::DriveY) > 0.9);
+    keyboard.release('w', at(KEY_HOLD.as_millis() as u64 * 3 + 10));
+    keyboard.tick(at(KEY_HOLD.as_millis() as u64 * 3 + 1000));
+    assert_eq!(keyboard.output(Action::DriveY), 0.0);
+}
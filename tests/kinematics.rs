@@ -0,0 +1,109 @@
+use nightmare_gs::{
+    protocol::DriveCommand,
+    teleop::{arcade, curvature, mecanum, tank, Action, ActionEvent, DriveInput, DriveMode, Kinematics, Wheels},
+};
+
+const FL: usize = DriveCommand::FRONT_LEFT;
+const FR: usize = DriveCommand::FRONT_RIGHT;
+const RL: usize = DriveCommand::REAR_LEFT;
+const RR: usize = DriveCommand::REAR_RIGHT;
+
+fn assert_wheels(actual: Wheels, expected: Wheels) {
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert!((actual - expected).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn tank_drives_each_side_from_its_own_stick() {
+    let wheels = tank(0.5, -0.25);
+    assert_eq!((wheels[FL], wheels[RL]), (0.5, 0.5));
+    assert_eq!((wheels[FR], wheels[RR]), (-0.25, -0.25));
+}
+
+#[test]
+fn arcade_mixes_and_keeps_ratios_when_saturated() {
+    assert_wheels(arcade(0.5, 0.0), [0.5; 4]);
+    // Turning clockwise speeds the left side up and slows the right.
+    let wheels = arcade(0.5, 0.25);
+    assert_eq!((wheels[FL], wheels[FR]), (0.75, 0.25));
+    // 1.5 and 0.5 scale down together rather than clipping the left side.
+    let wheels = arcade(1.0, 0.5);
+    assert_eq!((wheels[FL], wheels[FR]), (1.0, 1.0 / 3.0));
+    assert_eq!((wheels[RL], wheels[RR]), (1.0, 1.0 / 3.0));
+}
+
+#[test]
+fn curvature_turns_by_speed_unless_quick_turning() {
+    // Standing still, the turn stick does nothing...
+    assert_wheels(curvature(0.0, 1.0, false), [0.0; 4]);
+    // ...unless quick turn is held, which spins on the spot.
+    let wheels = curvature(0.0, 1.0, true);
+    assert_eq!((wheels[FL], wheels[FR]), (1.0, -1.0));
+    // Half speed with full turn arcs half as hard as arcade would.
+    let wheels = curvature(0.5, 1.0, false);
+    assert_eq!((wheels[FL], wheels[FR]), (1.0, 0.0));
+    let wheels = curvature(0.5, 0.5, false);
+    assert_eq!((wheels[FL], wheels[FR]), (0.75, 0.25));
+}
+
+#[test]
+fn mecanum_strafes_turns_and_normalises() {
+    assert_wheels(mecanum(0.0, 1.0, 0.0, None), [1.0; 4]);
+    // Strafing right runs the diagonals against each other.
+    let wheels = mecanum(1.0, 0.0, 0.0, None);
+    assert_wheels(wheels, {
+        let mut expected = [0.0; 4];
+        expected[FL] = 1.0;
+        expected[FR] = -1.0;
+        expected[RL] = -1.0;
+        expected[RR] = 1.0;
+        expected
+    });
+    let wheels = mecanum(0.0, 0.0, 0.5, None);
+    assert_eq!((wheels[FL], wheels[RL], wheels[FR], wheels[RR]), (0.5, 0.5, -0.5, -0.5));
+    // Forward, strafe and turn together would reach 3 on the front left.
+    let wheels = mecanum(1.0, 1.0, 1.0, None);
+    assert!(wheels.iter().all(|wheel| wheel.abs() <= 1.0));
+    assert_eq!(wheels[FL], 1.0);
+}
+
+#[test]
+fn field_oriented_mecanum_rotates_by_heading() {
+    // Facing right, pushing field-forward strafes the robot to its left.
+    assert_wheels(mecanum(0.0, 1.0, 0.0, Some(90.0)), mecanum(-1.0, 0.0, 0.0, None));
+    // Facing backwards, field-forward is robot-backward.
+    assert_wheels(mecanum(0.0, 0.5, 0.0, Some(180.0)), mecanum(0.0, -0.5, 0.0, None));
+    assert_wheels(mecanum(0.3, 0.4, 0.2, Some(0.0)), mecanum(0.3, 0.4, 0.2, None));
+}
+
+#[test]
+fn drive_input_mixes_through_the_selected_mode() {
+    let mut drive = DriveInput::new();
+    assert_eq!(drive.kinematics().mode, DriveMode::Arcade);
+    drive.update(&ActionEvent::Axis(Action::DriveY, 0.5));
+    drive.update(&ActionEvent::Axis(Action::DriveTurn, 0.25));
+    drive.update(&ActionEvent::Axis(Action::ArmLift, -1.0));
+    let command = drive.command();
+    assert_eq!((command.wheels[FL], command.wheels[FR], command.arm), (0.75, 0.25, -1.0));
+
+    assert!(drive.set_kinematics(Kinematics { mode: DriveMode::Tank, ..Kinematics::default() }));
+    drive.update(&ActionEvent::Axis(Action::TankRight, -0.5));
+    assert_eq!((drive.command().wheels[FL], drive.command().wheels[FR]), (0.5, -0.5));
+
+    drive.set_kinematics(Kinematics { mode: DriveMode::Curvature, ..Kinematics::default() });
+    drive.update(&ActionEvent::Axis(Action::DriveY, 0.0));
+    assert_eq!(drive.command().wheels, [0.0; 4]);
+    assert!(drive.update(&ActionEvent::Pressed(Action::QuickTurn)));
+    assert_eq!(drive.command().wheels[FL], 0.25);
+    assert!(drive.update(&ActionEvent::Released(Action::QuickTurn)));
+
+    // Field orientation only takes effect in mecanum and once a heading is known.
+    drive.set_kinematics(Kinematics { mode: DriveMode::Mecanum, field_oriented: true });
+    drive.update(&ActionEvent::Axis(Action::DriveY, 1.0));
+    drive.update(&ActionEvent::Axis(Action::DriveTurn, 0.0));
+    assert_wheels(drive.command().wheels, [1.0; 4]);
+    assert!(drive.set_heading(Some(90.0)));
+    assert_wheels(drive.command().wheels, mecanum(-1.0, 0.0, 0.0, None));
+    assert_eq!(Kinematics { mode: DriveMode::Mecanum, field_oriented: true }.describe(), "mecanum (field oriented)");
+}
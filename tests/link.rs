@@ -0,0 +1,40 @@
+use std::{io::{self, Read, Write}, sync::Arc, thread, time::Duration};
+
+use nightmare_gs::tasks::Link;
+use tokio::time;
+
+/// A port end that never has anything to read, timing out like a real one.
+struct Quiet {
+    _port: Arc<()>,
+}
+
+impl Read for Quiet {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        thread::sleep(Duration::from_millis(10));
+        Err(io::ErrorKind::TimedOut.into())
+    }
+}
+
+struct Sink {
+    _port: Arc<()>,
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn closing_a_link_lets_go_of_the_port() {
+    let port = Arc::new(());
+    let link = Link::new(Quiet { _port: port.clone() }, Sink { _port: port.clone() });
+    link.send(b"hello".to_vec()).unwrap();
+    time::timeout(Duration::from_secs(1), link.close()).await.expect("close should finish within a read timeout");
+    // Both threads have ended and dropped their halves of the port.
+    assert_eq!(Arc::strong_count(&port), 1);
+}
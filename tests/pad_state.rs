@@ -0,0 +1,60 @@
+use std::time::{Duration, SystemTime};
+
+use gilrs::{Axis, Button, EventType as GamepadEventType};
+
+use nightmare_gs::teleop::{PadState, RATE_WINDOW};
+
+fn at(ms: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+}
+
+#[test]
+fn keeps_every_input_not_just_the_last() {
+    let mut pad = PadState::new();
+    assert!(!pad.is_connected());
+    pad.set_axis(Axis::LeftStickX, 0.5, at(0));
+    pad.set_axis(Axis::RightStickY, -1.0, at(1));
+    pad.set_button(Button::South, 1.0, at(2));
+    pad.set_button(Button::RightTrigger2, 0.3, at(3));
+    assert!(pad.is_connected());
+    assert_eq!(pad.axis(Axis::LeftStickX), 0.5);
+    assert_eq!(pad.axis(Axis::RightStickY), -1.0);
+    assert_eq!(pad.axis(Axis::LeftStickY), 0.0);
+    assert!(pad.is_pressed(Button::South));
+    // A trigger pulled partway has a value but doesn't read as pressed.
+    assert_eq!(pad.button(Button::RightTrigger2), 0.3);
+    assert!(!pad.is_pressed(Button::RightTrigger2));
+    pad.set_button(Button::South, 0.0, at(4));
+    assert!(!pad.is_pressed(Button::South));
+    assert_eq!(pad.axis(Axis::LeftStickX), 0.5);
+}
+
+#[test]
+fn disconnecting_clears_everything_held() {
+    let mut pad = PadState::new();
+    pad.set_axis(Axis::LeftStickY, 1.0, at(0));
+    pad.set_button(Button::East, 1.0, at(0));
+    pad.apply(&GamepadEventType::Disconnected, at(10));
+    assert!(!pad.is_connected());
+    assert_eq!(pad.axis(Axis::LeftStickY), 0.0);
+    assert!(!pad.is_pressed(Button::East));
+    assert_eq!(pad.since_last_event(at(110)), Some(Duration::from_millis(100)));
+    pad.apply(&GamepadEventType::Connected, at(20));
+    assert!(pad.is_connected());
+}
+
+#[test]
+fn event_rate_covers_the_recent_window() {
+    let mut pad = PadState::new();
+    assert_eq!(pad.event_rate(at(0)), 0.0);
+    assert_eq!(pad.since_last_event(at(0)), None);
+    for index in 0..50 {
+        pad.set_axis(Axis::LeftStickX, index as f32 / 50.0, at(index * 20));
+    }
+    let last = at(49 * 20);
+    assert_eq!(pad.event_rate(last), 50.0 / RATE_WINDOW.as_secs_f32());
+    assert_eq!(pad.since_last_event(last + Duration::from_millis(250)), Some(Duration::from_millis(250)));
+    // Half a window later only the newer half still counts, and a window after that nothing does.
+    assert_eq!(pad.event_rate(last + RATE_WINDOW / 2 + Duration::from_millis(10)), 25.0 / RATE_WINDOW.as_secs_f32());
+    assert_eq!(pad.event_rate(last + RATE_WINDOW * 2), 0.0);
+}
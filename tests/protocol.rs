@@ -0,0 +1,94 @@
+use nightmare_gs::protocol::{cobs, crc16, crc32, FrameDecoder, Message, Packet, ProtocolError, MAX_FRAME_LEN};
+
+/// Re-frames raw (header, payload and CRC) bytes the way the wire carries them.
+fn frame(raw: &[u8]) -> Vec<u8> {
+    let mut frame = cobs::encode(raw);
+    frame.push(0);
+    frame
+}
+
+#[test]
+fn cobs_round_trips_zeros_and_long_runs() {
+    let cases: Vec<Vec<u8>> = vec![
+        vec![],
+        vec![0],
+        vec![0, 0, 0],
+        vec![1, 0, 2, 0, 0, 3],
+        (1..=254).collect(),
+        (0..255).map(|byte| byte as u8 | 1).collect(),
+        std::iter::repeat_n(0x55, 600).chain([0]).chain(std::iter::repeat_n(0xAA, 300)).collect(),
+    ];
+    for data in cases {
+        let encoded = cobs::encode(&data);
+        assert!(!encoded.contains(&0), "zero in encoding of {} bytes", data.len());
+        assert_eq!(cobs::decode(&encoded).as_deref(), Some(data.as_slice()));
+    }
+    // A full 254-byte run needs no zero after it, so the next block follows directly.
+    let run: Vec<u8> = (1..=254).collect();
+    assert_eq!(cobs::encode(&run)[0], 0xFF);
+    assert_eq!(cobs::encode(&run).len(), 256);
+}
+
+#[test]
+fn cobs_rejects_codes_past_the_end() {
+    assert_eq!(cobs::decode(&[0x05, 1, 2]), None);
+    assert_eq!(cobs::decode(&[0x02, 1, 0x00]), None);
+}
+
+#[test]
+fn crcs_match_known_answers() {
+    assert_eq!(crc16(b"123456789"), 0x29B1);
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+}
+
+#[test]
+fn frames_round_trip() {
+    let packets = [
+        Packet::new(0, Message::Hello),
+        Packet::new(7, Message::Heartbeat(0)),
+        Packet::new(65535, Message::Nack { ack_seq: 3, code: 0 }),
+        Packet::new(8, Message::BootEnter { base_address: None }),
+        Packet::new(9, Message::BootEnter { base_address: Some(0x0800_4000) }),
+    ];
+    let wire: Vec<u8> = packets.iter().flat_map(Packet::encode).collect();
+    let decoded: Vec<_> = FrameDecoder::new().decode(&wire);
+    assert_eq!(decoded, packets.into_iter().map(Ok).collect::<Vec<_>>());
+}
+
+#[test]
+fn frame_split_across_reads_decodes_once_complete() {
+    let packet = Packet::new(42, Message::Heartbeat(0x0102_0300));
+    let wire = packet.encode();
+    let mut decoder = FrameDecoder::new();
+    let (first, second) = wire.split_at(wire.len() / 2);
+    assert!(decoder.decode(first).is_empty());
+    assert_eq!(decoder.decode(second), vec![Ok(packet)]);
+}
+
+#[test]
+fn corrupt_frames_are_typed_errors() {
+    let wire = Packet::new(1, Message::Heartbeat(99)).encode();
+    let mut raw = cobs::decode(&wire[..wire.len() - 1]).unwrap();
+    // Flip a payload bit, leaving the CRC as sent.
+    let payload = raw.len() - 3;
+    raw[payload] ^= 0x40;
+    let mut decoder = FrameDecoder::new();
+    assert!(matches!(decoder.decode(&frame(&raw))[..], [Err(ProtocolError::BadCrc { .. })]));
+
+    // Shorter than a header and a CRC.
+    assert_eq!(decoder.decode(&frame(&[0x01, 0x02, 0x03])), vec![Err(ProtocolError::TooShort(3))]);
+    assert_eq!(decoder.decode(&[0x05, 1, 0]), vec![Err(ProtocolError::InvalidCobs)]);
+
+    // A valid CRC around an id nobody knows.
+    let mut raw = vec![0xEE, 0, 0];
+    raw.extend_from_slice(&crc16(&raw).to_le_bytes());
+    assert_eq!(decoder.decode(&frame(&raw)), vec![Err(ProtocolError::UnknownMessage(0xEE))]);
+
+    // An oversized frame is dropped without disturbing the one after it.
+    let mut wire = vec![0x11; MAX_FRAME_LEN + 10];
+    wire.push(0);
+    wire.extend(Packet::new(2, Message::Hello).encode());
+    let decoded = decoder.decode(&wire);
+    assert!(matches!(decoded[0], Err(ProtocolError::Oversized(_))));
+    assert_eq!(decoded[1], Ok(Packet::new(2, Message::Hello)));
+}
@@ -0,0 +1,58 @@
+use std::time::SystemTime;
+
+use nightmare_gs::{
+    pages::{LogAction, RobotLog},
+    protocol::{FrameDecoder, LogLevel, Message, Packet},
+    tasks::LogEntry,
+};
+
+fn entry(level: LogLevel, tag: &str, text: &str) -> LogEntry {
+    LogEntry { robot_us: 0, at: SystemTime::now(), level, tag: tag.to_string(), text: text.to_string() }
+}
+
+fn visible_texts(log: &RobotLog) -> Vec<String> {
+    log.visible().map(|(_, entry)| entry.text.clone()).collect()
+}
+
+#[test]
+fn log_message_survives_the_wire() {
+    let packet = Packet::new(3, Message::Log {
+        robot_us: 42,
+        level: LogLevel::Warn,
+        tag: "motor".to_string(),
+        text: "left driver hot: 81.5 °C".to_string(),
+    });
+    let decoded = FrameDecoder::new().decode(&packet.encode());
+    assert_eq!(decoded, vec![Ok(packet)]);
+}
+
+#[test]
+fn log_is_filtered_by_level_and_search() {
+    let mut log = RobotLog::new();
+    log.add_entry("robot".to_string(), entry(LogLevel::Error, "imu", "no response"));
+    log.add_entry("robot".to_string(), entry(LogLevel::Info, "state", "Connected -> Enabled"));
+    log.add_entry("robot".to_string(), entry(LogLevel::Debug, "imu", "sample 123"));
+    assert_eq!(visible_texts(&log), ["no response", "Connected -> Enabled"]);
+
+    log.apply(LogAction::MinLevel(LogLevel::Trace));
+    log.apply(LogAction::Search("IMU".to_string()));
+    assert_eq!(visible_texts(&log), ["no response", "sample 123"]);
+
+    log.apply(LogAction::MinLevel(LogLevel::Error));
+    assert_eq!(visible_texts(&log), ["no response"]);
+
+    log.apply(LogAction::ClearSearch);
+    log.apply(LogAction::Clear);
+    assert!(visible_texts(&log).is_empty());
+}
+
+#[test]
+fn log_history_is_bounded() {
+    let mut log = RobotLog::new();
+    for i in 0..1500 {
+        log.add_entry("robot".to_string(), entry(LogLevel::Info, "count", &i.to_string()));
+    }
+    let texts = visible_texts(&log);
+    assert_eq!(texts.len(), 1000);
+    assert_eq!(texts[0], "500");
+}
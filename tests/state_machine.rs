@@ -0,0 +1,91 @@
+use std::time::SystemTime;
+
+use nightmare_gs::tasks::{DriverState::{self, *}, StateMachine};
+
+const STATES: [DriverState; 6] = [Active, Connected, Disabled, Enabled, Faulted, EStopped];
+
+fn machine_in(state: DriverState) -> StateMachine {
+    let mut machine = StateMachine::new();
+    machine.transition(state, "test setup");
+    assert_eq!(machine.state(), state);
+    machine
+}
+
+#[test]
+fn operator_requests_follow_the_table() {
+    // (from, to, accepted)
+    let table = [
+        (Active, Enabled, false),
+        (Active, Disabled, false),
+        (Active, EStopped, true),
+        (Connected, Enabled, false),
+        (Connected, Disabled, true),
+        (Connected, EStopped, true),
+        (Disabled, Enabled, true),
+        (Disabled, Disabled, false),
+        (Disabled, EStopped, true),
+        (Enabled, Disabled, true),
+        (Enabled, Enabled, false),
+        (Enabled, EStopped, true),
+        (Faulted, Disabled, false),
+        (Faulted, Enabled, false),
+        (Faulted, EStopped, true),
+        (EStopped, Disabled, false),
+        (EStopped, Enabled, false),
+        (EStopped, EStopped, true),
+    ];
+    for (from, to, accepted) in table {
+        let result = machine_in(from).check_request(to);
+        assert_eq!(result.is_ok(), accepted, "{} -> {}: {:?}", from, to, result);
+    }
+    // Link and fault states only ever come from the link or the robot.
+    for from in STATES {
+        for to in [Active, Connected, Faulted] {
+            assert!(machine_in(from).check_request(to).is_err(), "{} -> {}", from, to);
+        }
+    }
+}
+
+#[test]
+fn faulted_and_estopped_stay_latched_until_reset() {
+    for latched in [Faulted, EStopped] {
+        let machine = machine_in(latched);
+        assert!(latched.is_latched());
+        for to in [Active, Connected, Disabled, Enabled, Faulted] {
+            let reason = machine.check_request(to).unwrap_err();
+            if to != latched {
+                assert!(reason.contains("latched"), "{} -> {}: {}", latched, to, reason);
+            }
+        }
+        assert_eq!(machine.check_reset(), Ok(()));
+    }
+    for unlatched in [Active, Connected, Disabled, Enabled] {
+        assert!(!unlatched.is_latched());
+        assert!(machine_in(unlatched).check_reset().is_err(), "{} reset", unlatched);
+    }
+}
+
+#[test]
+fn transitions_record_time_and_reason() {
+    let mut machine = StateMachine::new();
+    assert_eq!(machine.state(), Active);
+    let steps = [
+        (Connected, "robot said hello"),
+        (Disabled, "operator request acked"),
+        (Enabled, "operator request acked"),
+        (EStopped, "e-stop requested by operator"),
+        (Connected, "latch reset by operator"),
+    ];
+    let mut from = Active;
+    for (to, reason) in steps {
+        let before = SystemTime::now();
+        let transition = machine.transition(to, reason).expect("state changed");
+        let after = SystemTime::now();
+        assert_eq!((transition.from, transition.to, transition.reason.as_str()), (from, to, reason));
+        assert!(before <= transition.at && transition.at <= after);
+        assert!(transition.to_string().ends_with(&format!("{} -> {}: {}", from, to, reason)));
+        from = to;
+    }
+    // Staying put is not a transition.
+    assert_eq!(machine.transition(Connected, "again"), None);
+}
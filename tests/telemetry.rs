@@ -0,0 +1,28 @@
+use nightmare_gs::protocol::{FaultFlags, FrameDecoder, Message, MotorTelemetry, Packet, RobotTelemetry};
+
+fn sample() -> RobotTelemetry {
+    RobotTelemetry {
+        battery_v: 12.345,
+        mcu_temperature_c: -4.5,
+        faults: FaultFlags(FaultFlags::OVERCURRENT | FaultFlags::ENCODER),
+        motors: vec![
+            MotorTelemetry { current_a: 1.5, encoder_ticks: -120_000, temperature_c: 41.2 },
+            MotorTelemetry { current_a: -0.25, encoder_ticks: 7, temperature_c: 39.0 },
+        ],
+        heading_deg: Some(-92.5),
+    }
+}
+
+#[test]
+fn telemetry_survives_the_wire() {
+    let packet = Packet::new(9, Message::Telemetry { robot_us: 1_234_567, telemetry: sample() });
+    let decoded = FrameDecoder::new().decode(&packet.encode());
+    assert_eq!(decoded, vec![Ok(packet)]);
+}
+
+#[test]
+fn fault_flags_are_named() {
+    assert_eq!(FaultFlags::default().to_string(), "none");
+    assert_eq!(sample().faults.to_string(), "overcurrent, encoder");
+    assert_eq!(FaultFlags(1 << 15).to_string(), "unknown 0x8000");
+}